@@ -0,0 +1,249 @@
+use crate::ChessBoard;
+use crate::pieces::Pieces;
+use crate::bitboard::{Bitboard, Square};
+use crate::util::Teams;
+
+//File and rank steps for each ray. The first four increase the square index, the last four decrease it.
+const RAY_STEPS: [(i8, i8); 8] = [(0, 1), (1, 1), (1, 0), (-1, 1), (0, -1), (-1, -1), (-1, 0), (1, -1)];
+const ROOK_RAYS: [usize; 4] = [0, 2, 4, 6];
+const BISHOP_RAYS: [usize; 4] = [1, 3, 5, 7];
+
+const KNIGHT_ATTACKS: [u64; 64] = generate_leaper_attacks(&[(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)]);
+const KING_ATTACKS: [u64; 64] = generate_leaper_attacks(&[(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)]);
+//Squares attacked by a pawn standing on each square, indexed by Teams::index
+const PAWN_ATTACKS: [[u64; 64]; 2] = [generate_leaper_attacks(&[(-1, 1), (1, 1)]), generate_leaper_attacks(&[(-1, -1), (1, -1)])];
+//Every square from (but not including) each square to the edge of the board, per ray direction
+const RAYS: [[u64; 64]; 8] = generate_rays();
+
+const fn generate_leaper_attacks(steps: &[(i8, i8)]) -> [u64; 64] {
+    let mut output = [0u64; 64];
+    let mut square = 0;
+    while square < 64 {
+        let mut i = 0;
+        while i < steps.len() {
+            let file = (square % 8) as i8 + steps[i].0;
+            let rank = (square / 8) as i8 + steps[i].1;
+            if file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+                output[square] |= 1 << (rank * 8 + file);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    return output;
+}
+
+const fn generate_rays() -> [[u64; 64]; 8] {
+    let mut output = [[0u64; 64]; 8];
+    let mut direction = 0;
+    while direction < 8 {
+        let mut square = 0;
+        while square < 64 {
+            let mut file = (square % 8) as i8 + RAY_STEPS[direction].0;
+            let mut rank = (square / 8) as i8 + RAY_STEPS[direction].1;
+            while file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+                output[direction][square] |= 1 << (rank * 8 + file);
+                file += RAY_STEPS[direction].0;
+                rank += RAY_STEPS[direction].1;
+            }
+            square += 1;
+        }
+        direction += 1;
+    }
+    return output;
+}
+
+//Squares along the ray up to and including the first blocker
+#[inline]
+fn ray_attacks(square: usize, direction: usize, occupied: u64) -> u64 {
+    let ray = RAYS[direction][square];
+    let blockers = ray & occupied;
+    if blockers == 0 {
+        return ray;
+    }
+    let blocker = if direction < 4 {
+        blockers.trailing_zeros()
+    } else {
+        63 - blockers.leading_zeros()
+    } as usize;
+    return ray ^ RAYS[direction][blocker];
+}
+
+#[inline]
+pub fn knight_attacks(square: Square) -> Bitboard {
+    return Bitboard(KNIGHT_ATTACKS[square.index()]);
+}
+
+#[inline]
+pub fn king_attacks(square: Square) -> Bitboard {
+    return Bitboard(KING_ATTACKS[square.index()]);
+}
+
+//Squares a pawn of the team standing on the square attacks
+#[inline]
+pub fn pawn_attacks(team: Teams, square: Square) -> Bitboard {
+    return Bitboard(PAWN_ATTACKS[team.index()][square.index()]);
+}
+
+#[inline]
+pub fn bishop_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    let mut output = 0;
+    for direction in BISHOP_RAYS {
+        output |= ray_attacks(square.index(), direction, occupied.0);
+    }
+    return Bitboard(output);
+}
+
+#[inline]
+pub fn rook_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    let mut output = 0;
+    for direction in ROOK_RAYS {
+        output |= ray_attacks(square.index(), direction, occupied.0);
+    }
+    return Bitboard(output);
+}
+
+#[inline]
+pub fn queen_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    return bishop_attacks(square, occupied) | rook_attacks(square, occupied);
+}
+
+//Squares attacked by a piece on the square, given the occupied squares
+#[inline]
+pub fn piece_attacks(piece: Pieces, team: Teams, square: Square, occupied: Bitboard) -> Bitboard {
+    return match piece {
+        Pieces::Pawn => pawn_attacks(team, square),
+        Pieces::Knight => knight_attacks(square),
+        Pieces::Bishop => bishop_attacks(square, occupied),
+        Pieces::Rook => rook_attacks(square, occupied),
+        Pieces::Queen => queen_attacks(square, occupied),
+        Pieces::King => king_attacks(square)
+    };
+}
+
+//Squares strictly between two squares on a shared line, or nothing if they don't share one
+pub fn between(from: Square, to: Square) -> Bitboard {
+    let (from, to) = (from.index(), to.index());
+    let target = 1u64 << to;
+    for direction in 0..8 {
+        if RAYS[direction][from] & target != 0 {
+            return Bitboard(RAYS[direction][from] & !RAYS[direction][to] & !target);
+        }
+    }
+    return Bitboard::EMPTY;
+}
+
+//The whole line through both squares, edge to edge, or nothing if they don't share one
+pub fn line(from: Square, to: Square) -> Bitboard {
+    let (from, to) = (from.index(), to.index());
+    let target = 1u64 << to;
+    for direction in 0..8 {
+        if RAYS[direction][from] & target != 0 {
+            return Bitboard(RAYS[direction][from] | RAYS[(direction + 4) % 8][from] | (1u64 << from));
+        }
+    }
+    return Bitboard::EMPTY;
+}
+
+impl ChessBoard {
+    #[inline]
+    pub fn team_pieces(&self, team: Teams) -> Bitboard {
+        let mut output = 0;
+        for piece in team.pieces() {
+            output |= self.board.0[piece];
+        }
+        return Bitboard(output);
+    }
+
+    #[inline]
+    pub fn side_to_move(&self) -> Teams {
+        return Teams::from(self.move_number);
+    }
+
+    //Where the team's king stands, if it has one
+    #[inline]
+    pub fn king_square(&self, team: Teams) -> Option<Square> {
+        return self.board.get_board(Pieces::King, team).first();
+    }
+
+    //Pieces of both teams attacking the square, with a custom occupancy for x-ray lookups
+    pub fn attackers_with_occupancy(&self, square: Square, occupied: Bitboard) -> Bitboard {
+        let board = &self.board;
+        let diagonal = board.get_board(Pieces::Bishop, Teams::White) | board.get_board(Pieces::Queen, Teams::White) |
+            board.get_board(Pieces::Bishop, Teams::Black) | board.get_board(Pieces::Queen, Teams::Black);
+        let straight = board.get_board(Pieces::Rook, Teams::White) | board.get_board(Pieces::Queen, Teams::White) |
+            board.get_board(Pieces::Rook, Teams::Black) | board.get_board(Pieces::Queen, Teams::Black);
+        let knights = board.get_board(Pieces::Knight, Teams::White) | board.get_board(Pieces::Knight, Teams::Black);
+        let kings = board.get_board(Pieces::King, Teams::White) | board.get_board(Pieces::King, Teams::Black);
+
+        return ((pawn_attacks(Teams::Black, square) & board.get_board(Pieces::Pawn, Teams::White)) |
+            (pawn_attacks(Teams::White, square) & board.get_board(Pieces::Pawn, Teams::Black)) |
+            (knight_attacks(square) & knights) |
+            (king_attacks(square) & kings) |
+            (bishop_attacks(square, occupied) & diagonal) |
+            (rook_attacks(square, occupied) & straight)) & occupied;
+    }
+
+    //Pieces of the team attacking the square
+    pub fn attackers_of(&self, square: Square, team: Teams) -> Bitboard {
+        return self.attackers_with_occupancy(square, self.board.occupied()) & self.team_pieces(team);
+    }
+
+    pub fn is_attacked(&self, square: Square, by: Teams) -> bool {
+        let board = &self.board;
+        let occupied = board.occupied();
+
+        //Cheapest lookups first, so most calls return early
+        return pawn_attacks(!by, square).intersects(board.get_board(Pieces::Pawn, by)) ||
+            knight_attacks(square).intersects(board.get_board(Pieces::Knight, by)) ||
+            king_attacks(square).intersects(board.get_board(Pieces::King, by)) ||
+            bishop_attacks(square, occupied).intersects(board.get_board(Pieces::Bishop, by) | board.get_board(Pieces::Queen, by)) ||
+            rook_attacks(square, occupied).intersects(board.get_board(Pieces::Rook, by) | board.get_board(Pieces::Queen, by));
+    }
+
+    //Every square the team attacks, whether or not a piece stands on it
+    pub fn attacked_squares(&self, team: Teams) -> Bitboard {
+        let occupied = self.board.occupied();
+        let mut output = Bitboard::EMPTY;
+        for piece in team.pieces() {
+            let piece_type = Pieces::from(piece as u8);
+            for square in Bitboard(self.board.0[piece]) {
+                output |= piece_attacks(piece_type, team, square, occupied);
+            }
+        }
+        return output;
+    }
+
+    //Enemy pieces giving check to the king of the side to move
+    pub fn checkers(&self) -> Bitboard {
+        let team = self.side_to_move();
+        return match self.king_square(team) {
+            Some(king) => self.attackers_of(king, !team),
+            None => Bitboard::EMPTY
+        };
+    }
+
+    //Pieces of the team that can't leave the line between their king and an enemy slider
+    pub fn pinned_pieces(&self, team: Teams) -> Bitboard {
+        let board = &self.board;
+        let king = match self.king_square(team) {
+            Some(king) => king,
+            None => return Bitboard::EMPTY
+        };
+        let enemy = !team;
+        let own = self.team_pieces(team);
+
+        //Enemy sliders that would attack the king on an empty board
+        let snipers = (bishop_attacks(king, Bitboard::EMPTY) & (board.get_board(Pieces::Bishop, enemy) | board.get_board(Pieces::Queen, enemy))) |
+            (rook_attacks(king, Bitboard::EMPTY) & (board.get_board(Pieces::Rook, enemy) | board.get_board(Pieces::Queen, enemy)));
+
+        let mut output = Bitboard::EMPTY;
+        for sniper in snipers {
+            let blockers = between(king, sniper) & board.occupied();
+            if blockers.is_single() && blockers.intersects(own) {
+                output |= blockers;
+            }
+        }
+        return output;
+    }
+}
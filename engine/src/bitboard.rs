@@ -0,0 +1,349 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+use crate::util::{Directions, Position};
+
+//One of the 64 squares, a1 = 0, b1 = 1, up to h8 = 63
+#[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Square(pub u8);
+
+//A column of the board, a = 0 up to h = 7
+#[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct File(pub u8);
+
+//A row of the board, the first rank = 0 up to the eighth = 7
+#[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Rank(pub u8);
+
+//A set of squares, one bit per square indexed like Square
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub struct Bitboard(pub u64);
+
+//The squares of a bitboard from a1 towards h8, found by scanning for the lowest set bit
+pub struct Squares(u64);
+
+impl Square {
+    #[inline]
+    pub fn new(file: File, rank: Rank) -> Self {
+        return Square(rank.0 * 8 + file.0);
+    }
+
+    #[inline]
+    pub fn index(&self) -> usize {
+        return self.0 as usize;
+    }
+
+    #[inline]
+    pub fn file(&self) -> File {
+        return File(self.0 % 8);
+    }
+
+    #[inline]
+    pub fn rank(&self) -> Rank {
+        return Rank(self.0 / 8);
+    }
+
+    #[inline]
+    pub fn bitboard(&self) -> Bitboard {
+        return Bitboard(1 << self.0);
+    }
+
+    //Reads a square name like e4
+    pub fn parse(name: &str) -> Option<Self> {
+        let bytes = name.as_bytes();
+        if bytes.len() != 2 || !(b'a'..=b'h').contains(&bytes[0]) || !(b'1'..=b'8').contains(&bytes[1]) {
+            return None;
+        }
+        return Some(Square::new(File(bytes[0] - b'a'), Rank(bytes[1] - b'1')));
+    }
+
+    //The square the given number of files and ranks away, if it's still on the board
+    pub fn offset(&self, files: i8, ranks: i8) -> Option<Self> {
+        let file = self.file().0 as i8 + files;
+        let rank = self.rank().0 as i8 + ranks;
+        if !(0..8).contains(&file) || !(0..8).contains(&rank) {
+            return None;
+        }
+        return Some(Square((rank * 8 + file) as u8));
+    }
+
+    //The same square seen from the other side of the board, a1 to a8
+    #[inline]
+    pub fn flip_rank(&self) -> Self {
+        return Square(self.0 ^ 56);
+    }
+}
+
+impl File {
+    #[inline]
+    pub fn bitboard(&self) -> Bitboard {
+        return Bitboard(0x0101_0101_0101_0101 << self.0);
+    }
+}
+
+impl Rank {
+    #[inline]
+    pub fn bitboard(&self) -> Bitboard {
+        return Bitboard(0xFF << (self.0 * 8));
+    }
+}
+
+impl Bitboard {
+    pub const EMPTY: Bitboard = Bitboard(0);
+    pub const FULL: Bitboard = Bitboard(!0);
+
+    #[inline]
+    pub fn count(&self) -> u32 {
+        return self.0.count_ones();
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        return self.0 == 0;
+    }
+
+    #[inline]
+    pub fn contains(&self, square: Square) -> bool {
+        return self.0 & (1 << square.0) != 0;
+    }
+
+    #[inline]
+    pub fn intersects(&self, other: Bitboard) -> bool {
+        return self.0 & other.0 != 0;
+    }
+
+    //Whether exactly one square is set
+    #[inline]
+    pub fn is_single(&self) -> bool {
+        return self.0 != 0 && self.0 & (self.0 - 1) == 0;
+    }
+
+    //The square closest to a1
+    #[inline]
+    pub fn first(&self) -> Option<Square> {
+        if self.0 == 0 {
+            return None;
+        }
+        return Some(Square(self.0.trailing_zeros() as u8));
+    }
+
+    //The square closest to h8
+    #[inline]
+    pub fn last(&self) -> Option<Square> {
+        if self.0 == 0 {
+            return None;
+        }
+        return Some(Square(63 - self.0.leading_zeros() as u8));
+    }
+
+    #[inline]
+    pub fn with(&self, square: Square) -> Self {
+        return Bitboard(self.0 | 1 << square.0);
+    }
+
+    #[inline]
+    pub fn without(&self, square: Square) -> Self {
+        return Bitboard(self.0 & !(1 << square.0));
+    }
+
+    #[inline]
+    pub fn squares(&self) -> Squares {
+        return Squares(self.0);
+    }
+
+    //Moves every square in the direction, dropping the ones that would leave the board or wrap onto the far file
+    pub fn shift(&self, direction: Directions) -> Self {
+        let value = direction as i8;
+        let shifted = if value < 0 {
+            self.0 >> -value
+        } else {
+            self.0 << value
+        };
+        //Files moved sideways, from -2 to 2
+        let files = (value + 26).rem_euclid(8) - 2;
+        let mut mask = !0u64;
+        for file in 0..files.unsigned_abs() {
+            mask &= !File(if files > 0 { file } else { 7 - file }).bitboard().0;
+        }
+        return Bitboard(shifted & mask);
+    }
+}
+
+impl Iterator for Squares {
+    type Item = Square;
+
+    #[inline]
+    fn next(&mut self) -> Option<Square> {
+        if self.0 == 0 {
+            return None;
+        }
+        let square = Square(self.0.trailing_zeros() as u8);
+        self.0 &= self.0 - 1;
+        return Some(square);
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.0.count_ones() as usize;
+        return (count, Some(count));
+    }
+}
+
+impl ExactSizeIterator for Squares {}
+
+impl IntoIterator for Bitboard {
+    type Item = Square;
+    type IntoIter = Squares;
+
+    fn into_iter(self) -> Squares {
+        return self.squares();
+    }
+}
+
+impl FromIterator<Square> for Bitboard {
+    fn from_iter<I: IntoIterator<Item = Square>>(squares: I) -> Self {
+        let mut output = Bitboard::EMPTY;
+        for square in squares {
+            output = output.with(square);
+        }
+        return output;
+    }
+}
+
+impl BitAnd for Bitboard {
+    type Output = Bitboard;
+
+    #[inline]
+    fn bitand(self, other: Bitboard) -> Bitboard {
+        return Bitboard(self.0 & other.0);
+    }
+}
+
+impl BitOr for Bitboard {
+    type Output = Bitboard;
+
+    #[inline]
+    fn bitor(self, other: Bitboard) -> Bitboard {
+        return Bitboard(self.0 | other.0);
+    }
+}
+
+impl BitXor for Bitboard {
+    type Output = Bitboard;
+
+    #[inline]
+    fn bitxor(self, other: Bitboard) -> Bitboard {
+        return Bitboard(self.0 ^ other.0);
+    }
+}
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    #[inline]
+    fn not(self) -> Bitboard {
+        return Bitboard(!self.0);
+    }
+}
+
+impl BitAndAssign for Bitboard {
+    #[inline]
+    fn bitand_assign(&mut self, other: Bitboard) {
+        self.0 &= other.0;
+    }
+}
+
+impl BitOrAssign for Bitboard {
+    #[inline]
+    fn bitor_assign(&mut self, other: Bitboard) {
+        self.0 |= other.0;
+    }
+}
+
+impl BitXorAssign for Bitboard {
+    #[inline]
+    fn bitxor_assign(&mut self, other: Bitboard) {
+        self.0 ^= other.0;
+    }
+}
+
+impl From<u64> for Bitboard {
+    fn from(bits: u64) -> Self {
+        return Bitboard(bits);
+    }
+}
+
+impl From<Bitboard> for u64 {
+    fn from(bitboard: Bitboard) -> Self {
+        return bitboard.0;
+    }
+}
+
+impl From<Square> for Bitboard {
+    fn from(square: Square) -> Self {
+        return square.bitboard();
+    }
+}
+
+impl From<Position> for Bitboard {
+    fn from(position: Position) -> Self {
+        return Bitboard(position.0);
+    }
+}
+
+impl From<Square> for Position {
+    fn from(square: Square) -> Self {
+        return Position(1 << square.0);
+    }
+}
+
+//The square of a position with exactly one bit set
+impl TryFrom<Position> for Square {
+    type Error = String;
+
+    fn try_from(position: Position) -> Result<Self, String> {
+        return match position.0.count_ones() {
+            1 => Ok(Square(position.0.trailing_zeros() as u8)),
+            0 => Err(String::from("An empty position isn't a square")),
+            count => Err(format!("A position with {} squares isn't a single square", count))
+        };
+    }
+}
+
+//A square name like e4
+impl TryFrom<&str> for Square {
+    type Error = String;
+
+    fn try_from(name: &str) -> Result<Self, String> {
+        return Square::parse(name).ok_or_else(|| format!("{} isn't a square", name));
+    }
+}
+
+impl Display for Square {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "{}{}", self.file(), self.rank());
+    }
+}
+
+impl Display for File {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "{}", (b'a' + self.0) as char);
+    }
+}
+
+impl Display for Rank {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "{}", (b'1' + self.0) as char);
+    }
+}
+
+//Eighth rank first, like a board diagram, with 1 for set squares
+impl Display for Bitboard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for rank in (0..8).rev() {
+            for file in 0..8 {
+                write!(f, "{}", if self.contains(Square::new(File(file), Rank(rank))) { '1' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        return Ok(());
+    }
+}
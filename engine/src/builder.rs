@@ -0,0 +1,157 @@
+use std::fmt::Display;
+use crate::ChessBoard;
+use crate::bitboard::Square;
+use crate::game::Game;
+use crate::movegen::castle_index;
+use crate::pieces::Pieces;
+use crate::util::{Board, CastleDirection, GameStatus, Teams};
+use crate::validate::{MAX_HALFMOVE_CLOCK, PositionProblem};
+
+//Sets up a position piece by piece, keeping the occupancy board in sync, and checks it when it's built
+#[derive(Clone)]
+pub struct PositionBuilder {
+    board: Board,
+    side_to_move: Teams,
+    castle_status: [bool; 4],
+    //The square behind the pawn that just moved two squares, as FEN gives it
+    en_passant: Option<Square>,
+    halfmove_clock: u8,
+    fullmove_number: u16,
+}
+
+impl PositionBuilder {
+    //An empty board with white to move and no castling rights
+    pub fn new() -> Self {
+        return Self {
+            board: Board([0; 13]),
+            side_to_move: Teams::White,
+            castle_status: [false; 4],
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        };
+    }
+
+    //Puts the piece on the square, replacing whatever was there. Squares can be names like "e4", which fail
+    //if they aren't on the board.
+    pub fn place<S: TryInto<Square>>(self, square: S, piece: Pieces, team: Teams) -> Result<Self, String> where S::Error: Display {
+        let square = to_square(square)?;
+        let mut output = self.remove(square)?;
+        output.board.0[piece as usize + team as usize] |= square.bitboard().0;
+        output.board.0[12] |= square.bitboard().0;
+        return Ok(output);
+    }
+
+    pub fn remove<S: TryInto<Square>>(mut self, square: S) -> Result<Self, String> where S::Error: Display {
+        let square = to_square(square)?;
+        for pieces in self.board.0.iter_mut() {
+            *pieces &= !square.bitboard().0;
+        }
+        return Ok(self);
+    }
+
+    pub fn clear(mut self) -> Self {
+        self.board = Board([0; 13]);
+        return self;
+    }
+
+    pub fn side_to_move(mut self, team: Teams) -> Self {
+        self.side_to_move = team;
+        return self;
+    }
+
+    pub fn castling(mut self, team: Teams, direction: CastleDirection, allowed: bool) -> Self {
+        self.castle_status[castle_index(team, direction)] = allowed;
+        return self;
+    }
+
+    //The square a pawn capturing en passant would move to, like e3 after e2e4
+    pub fn en_passant<S: TryInto<Square>>(mut self, square: S) -> Result<Self, String> where S::Error: Display {
+        self.en_passant = Some(to_square(square)?);
+        return Ok(self);
+    }
+
+    pub fn clear_en_passant(mut self) -> Self {
+        self.en_passant = None;
+        return self;
+    }
+
+    //Plies since the last capture or pawn move, up to 150 as in FEN. The board's counter stops at 100,
+    //so build_game is needed for the seventy-five move rule to see more.
+    pub fn halfmove_clock(mut self, halfmoves: u8) -> Self {
+        self.halfmove_clock = halfmoves;
+        return self;
+    }
+
+    //Starting at 1 and going up after each black move, as FEN counts. The plies it adds up to have to fit in a u16.
+    pub fn fullmove_number(mut self, fullmoves: u16) -> Self {
+        self.fullmove_number = fullmoves;
+        return self;
+    }
+
+    //The position, or everything wrong with it
+    pub fn build(&self) -> Result<ChessBoard, Vec<PositionProblem>> {
+        let team = self.side_to_move;
+        let move_number = (self.fullmove_number.max(1) - 1).checked_mul(2).and_then(|plies| plies.checked_add(team.index() as u16));
+        let mut output = ChessBoard {
+            board: self.board.clone(),
+            move_number: move_number.unwrap_or(0),
+            fifty_move_counter: 100u8.saturating_sub(self.halfmove_clock),
+            castle_status: self.castle_status,
+            //A square with no room for the pawn in front of it is kept as is, for validation to report
+            last_pawn: self.en_passant.map(|target| target.offset(0, match team {
+                Teams::White => -1,
+                Teams::Black => 1
+            }).unwrap_or(target)),
+            game_status: GameStatus::Ongoing,
+            history: Vec::new(),
+            hash: 0,
+        };
+        let mut problems = output.validate();
+        if self.halfmove_clock > MAX_HALFMOVE_CLOCK {
+            problems.push(PositionProblem::HalfmoveClockOutOfRange);
+        }
+        if move_number.is_none() {
+            problems.push(PositionProblem::FullmoveNumberOutOfRange);
+        }
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+        output.hash = output.compute_hash();
+        output.history.push(output.hash);
+        return Ok(output);
+    }
+
+    //A game starting from the position, keeping the whole halfmove clock
+    pub fn build_game(&self) -> Result<Game, Vec<PositionProblem>> {
+        return Ok(Game::with_halfmove_clock(self.build()?, self.halfmove_clock as u32));
+    }
+}
+
+impl Default for PositionBuilder {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+//Starts from an existing position, for editing it
+impl From<&ChessBoard> for PositionBuilder {
+    fn from(board: &ChessBoard) -> Self {
+        let team = board.side_to_move();
+        return Self {
+            board: board.board.clone(),
+            side_to_move: team,
+            castle_status: board.castle_status,
+            en_passant: board.last_pawn.and_then(|pawn| pawn.offset(0, match team {
+                Teams::White => 1,
+                Teams::Black => -1
+            })),
+            halfmove_clock: 100 - board.fifty_move_counter.min(100),
+            fullmove_number: board.move_number / 2 + 1,
+        };
+    }
+}
+
+fn to_square<S: TryInto<Square>>(square: S) -> Result<Square, String> where S::Error: Display {
+    return square.try_into().map_err(|error| error.to_string());
+}
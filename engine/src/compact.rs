@@ -0,0 +1,116 @@
+use std::fmt::{Display, Formatter};
+use crate::ChessBoard;
+use crate::bitboard::Square;
+use crate::pieces::Pieces;
+use crate::util::{Move, Teams};
+
+//The top four bits. Promotions set the high flag bit, with the piece minus one (knight to queen) below it.
+const NORMAL: u16 = 0;
+const CASTLE: u16 = 1;
+const EN_PASSANT: u16 = 2;
+const PROMOTION: u16 = 0b1000;
+
+//A move packed into 16 bits for tables, books and game files: bits 0-5 from, 6-11 to, and 12-15 flags.
+//The piece and team aren't stored, so turning it back into a move needs the position it was made in.
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub struct CompactMove(pub u16);
+
+impl CompactMove {
+    //No move, for passing the turn or an empty slot. a1 to a1 can't be a real move.
+    pub const NULL: CompactMove = CompactMove(0);
+
+    //Packs a move made in the position
+    pub fn new(board: &ChessBoard, chess_move: &Move) -> Self {
+        let flags = if let Some(promotion) = chess_move.promotion {
+            PROMOTION | (promotion as u16 - 1)
+        } else if chess_move.piece == Pieces::King && chess_move.from.0.abs_diff(chess_move.to.0) == 2 {
+            CASTLE
+        } else if chess_move.piece == Pieces::Pawn && chess_move.from.file() != chess_move.to.file() &&
+            !board.board.occupied().contains(chess_move.to) {
+            EN_PASSANT
+        } else {
+            NORMAL
+        };
+        return CompactMove(chess_move.from.0 as u16 | (chess_move.to.0 as u16) << 6 | flags << 12);
+    }
+
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        return *self == CompactMove::NULL;
+    }
+
+    #[inline]
+    pub fn from(&self) -> Square {
+        return Square((self.0 & 0b111111) as u8);
+    }
+
+    #[inline]
+    pub fn to(&self) -> Square {
+        return Square((self.0 >> 6 & 0b111111) as u8);
+    }
+
+    #[inline]
+    fn flags(&self) -> u16 {
+        return self.0 >> 12;
+    }
+
+    pub fn promotion(&self) -> Option<Pieces> {
+        if self.flags() & !0b11 != PROMOTION {
+            return None;
+        }
+        return Some(Pieces::from((self.flags() & 0b11) as u8 + 1));
+    }
+
+    #[inline]
+    pub fn is_castle(&self) -> bool {
+        return self.flags() == CASTLE;
+    }
+
+    #[inline]
+    pub fn is_en_passant(&self) -> bool {
+        return self.flags() == EN_PASSANT;
+    }
+
+    //Unpacks the move for the position it was made in. Gives None for the null move, or when the position
+    //doesn't have a piece of the side to move that fits the flags, like after a hash collision.
+    pub fn decode(&self, board: &ChessBoard) -> Option<Move> {
+        if self.is_null() {
+            return None;
+        }
+        let team = board.side_to_move();
+        let (piece, owner) = board.piece_at(self.from())?;
+        if owner != team || board.piece_at(self.to()).is_some_and(|(_, target)| target == team) {
+            return None;
+        }
+        let fits = match self.flags() {
+            NORMAL => true,
+            CASTLE => piece == Pieces::King,
+            EN_PASSANT => piece == Pieces::Pawn,
+            _ => piece == Pieces::Pawn && self.promotion().is_some()
+        };
+        if !fits {
+            return None;
+        }
+        return Some(Move {
+            piece,
+            team,
+            from: self.from(),
+            to: self.to(),
+            promotion: self.promotion(),
+        });
+    }
+}
+
+//Coordinate notation like the full move, with 0000 for the null move as UCI writes it
+impl Display for CompactMove {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_null() {
+            return write!(f, "0000");
+        }
+        write!(f, "{}{}", self.from(), self.to())?;
+        if let Some(promotion) = self.promotion() {
+            write!(f, "{}", promotion.letter(Teams::Black))?;
+        }
+        return Ok(());
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::OnceLock;
+use crate::ChessBoard;
+use crate::game::Game;
+use crate::pgn::PgnGame;
+use crate::util::Teams;
+
+//The main lines of the common openings, in the format of scid's eco files: the code, the name in quotes,
+//then the moves. Anything more detailed can be loaded with OpeningTable::load.
+const OPENINGS: &str = r#"
+A00 "Polish Opening" 1. b4
+A00 "Grob Opening" 1. g4
+A00 "Van't Kruijs Opening" 1. e3
+A01 "Nimzo-Larsen Attack" 1. b3
+A02 "Bird's Opening" 1. f4
+A03 "Bird's Opening: Dutch Variation" 1. f4 d5
+A04 "Reti Opening" 1. Nf3
+A05 "Reti Opening" 1. Nf3 Nf6
+A06 "Reti Opening" 1. Nf3 d5
+A07 "King's Indian Attack" 1. Nf3 d5 2. g3
+A10 "English Opening" 1. c4
+A13 "English Opening" 1. c4 e6
+A15 "English Opening: Anglo-Indian Defence" 1. c4 Nf6
+A16 "English Opening: Anglo-Indian Defence" 1. c4 Nf6 2. Nc3
+A20 "English Opening: King's English" 1. c4 e5
+A30 "English Opening: Symmetrical Variation" 1. c4 c5
+A40 "Queen's Pawn Game" 1. d4
+A41 "Queen's Pawn Game" 1. d4 d6
+A43 "Old Benoni Defence" 1. d4 c5
+A45 "Indian Defence" 1. d4 Nf6
+A46 "Indian Defence" 1. d4 Nf6 2. Nf3
+A48 "East Indian Defence" 1. d4 Nf6 2. Nf3 g6
+A50 "Indian Defence" 1. d4 Nf6 2. c4
+A51 "Budapest Gambit" 1. d4 Nf6 2. c4 e5
+A52 "Budapest Gambit" 1. d4 Nf6 2. c4 e5 3. dxe5 Ng4
+A53 "Old Indian Defence" 1. d4 Nf6 2. c4 d6
+A56 "Benoni Defence" 1. d4 Nf6 2. c4 c5
+A57 "Benko Gambit" 1. d4 Nf6 2. c4 c5 3. d5 b5
+A60 "Modern Benoni" 1. d4 Nf6 2. c4 c5 3. d5 e6
+A80 "Dutch Defence" 1. d4 f5
+A84 "Dutch Defence" 1. d4 f5 2. c4
+B00 "King's Pawn Opening" 1. e4
+B00 "Nimzowitsch Defence" 1. e4 Nc6
+B01 "Scandinavian Defence" 1. e4 d5
+B02 "Alekhine's Defence" 1. e4 Nf6
+B06 "Modern Defence" 1. e4 g6
+B07 "Pirc Defence" 1. e4 d6 2. d4 Nf6
+B10 "Caro-Kann Defence" 1. e4 c6
+B12 "Caro-Kann Defence: Advance Variation" 1. e4 c6 2. d4 d5 3. e5
+B13 "Caro-Kann Defence: Exchange Variation" 1. e4 c6 2. d4 d5 3. exd5 cxd5
+B15 "Caro-Kann Defence" 1. e4 c6 2. d4 d5 3. Nc3
+B20 "Sicilian Defence" 1. e4 c5
+B21 "Sicilian Defence: Smith-Morra Gambit" 1. e4 c5 2. d4
+B22 "Sicilian Defence: Alapin Variation" 1. e4 c5 2. c3
+B23 "Sicilian Defence: Closed" 1. e4 c5 2. Nc3
+B27 "Sicilian Defence" 1. e4 c5 2. Nf3
+B30 "Sicilian Defence" 1. e4 c5 2. Nf3 Nc6
+B33 "Sicilian Defence: Sveshnikov Variation" 1. e4 c5 2. Nf3 Nc6 3. d4 cxd4 4. Nxd4 Nf6 5. Nc3 e5
+B40 "Sicilian Defence" 1. e4 c5 2. Nf3 e6
+B50 "Sicilian Defence" 1. e4 c5 2. Nf3 d6
+B54 "Sicilian Defence" 1. e4 c5 2. Nf3 d6 3. d4 cxd4 4. Nxd4
+B56 "Sicilian Defence" 1. e4 c5 2. Nf3 d6 3. d4 cxd4 4. Nxd4 Nf6 5. Nc3
+B70 "Sicilian Defence: Dragon Variation" 1. e4 c5 2. Nf3 d6 3. d4 cxd4 4. Nxd4 Nf6 5. Nc3 g6
+B90 "Sicilian Defence: Najdorf Variation" 1. e4 c5 2. Nf3 d6 3. d4 cxd4 4. Nxd4 Nf6 5. Nc3 a6
+C00 "French Defence" 1. e4 e6
+C01 "French Defence: Exchange Variation" 1. e4 e6 2. d4 d5 3. exd5 exd5
+C02 "French Defence: Advance Variation" 1. e4 e6 2. d4 d5 3. e5
+C03 "French Defence: Tarrasch Variation" 1. e4 e6 2. d4 d5 3. Nd2
+C10 "French Defence" 1. e4 e6 2. d4 d5 3. Nc3
+C11 "French Defence: Classical Variation" 1. e4 e6 2. d4 d5 3. Nc3 Nf6
+C15 "French Defence: Winawer Variation" 1. e4 e6 2. d4 d5 3. Nc3 Bb4
+C20 "King's Pawn Game" 1. e4 e5
+C21 "Centre Game" 1. e4 e5 2. d4 exd4
+C23 "Bishop's Opening" 1. e4 e5 2. Bc4
+C25 "Vienna Game" 1. e4 e5 2. Nc3
+C30 "King's Gambit" 1. e4 e5 2. f4
+C33 "King's Gambit Accepted" 1. e4 e5 2. f4 exf4
+C40 "King's Knight Opening" 1. e4 e5 2. Nf3
+C41 "Philidor Defence" 1. e4 e5 2. Nf3 d6
+C42 "Petrov's Defence" 1. e4 e5 2. Nf3 Nf6
+C44 "King's Pawn Game" 1. e4 e5 2. Nf3 Nc6
+C44 "Scotch Game" 1. e4 e5 2. Nf3 Nc6 3. d4
+C45 "Scotch Game" 1. e4 e5 2. Nf3 Nc6 3. d4 exd4 4. Nxd4
+C46 "Three Knights Opening" 1. e4 e5 2. Nf3 Nc6 3. Nc3
+C47 "Four Knights Game" 1. e4 e5 2. Nf3 Nc6 3. Nc3 Nf6
+C50 "Italian Game" 1. e4 e5 2. Nf3 Nc6 3. Bc4
+C50 "Giuoco Piano" 1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5
+C51 "Evans Gambit" 1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. b4
+C53 "Giuoco Piano" 1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. c3
+C55 "Two Knights Defence" 1. e4 e5 2. Nf3 Nc6 3. Bc4 Nf6
+C57 "Two Knights Defence: Knight Attack" 1. e4 e5 2. Nf3 Nc6 3. Bc4 Nf6 4. Ng5
+C60 "Ruy Lopez" 1. e4 e5 2. Nf3 Nc6 3. Bb5
+C60 "Ruy Lopez: Morphy Defence" 1. e4 e5 2. Nf3 Nc6 3. Bb5 a6
+C65 "Ruy Lopez: Berlin Defence" 1. e4 e5 2. Nf3 Nc6 3. Bb5 Nf6
+C68 "Ruy Lopez: Exchange Variation" 1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Bxc6
+C70 "Ruy Lopez" 1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4
+C78 "Ruy Lopez" 1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. O-O
+C84 "Ruy Lopez: Closed" 1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. O-O Be7
+D00 "Queen's Pawn Game" 1. d4 d5
+D00 "London System" 1. d4 d5 2. Bf4
+D02 "Queen's Pawn Game" 1. d4 d5 2. Nf3
+D06 "Queen's Gambit" 1. d4 d5 2. c4
+D07 "Queen's Gambit Declined: Chigorin Defence" 1. d4 d5 2. c4 Nc6
+D08 "Albin Countergambit" 1. d4 d5 2. c4 e5
+D10 "Slav Defence" 1. d4 d5 2. c4 c6
+D20 "Queen's Gambit Accepted" 1. d4 d5 2. c4 dxc4
+D30 "Queen's Gambit Declined" 1. d4 d5 2. c4 e6
+D35 "Queen's Gambit Declined" 1. d4 d5 2. c4 e6 3. Nc3 Nf6
+D43 "Semi-Slav Defence" 1. d4 d5 2. c4 c6 3. Nf3 Nf6 4. Nc3 e6
+D80 "Grunfeld Defence" 1. d4 Nf6 2. c4 g6 3. Nc3 d5
+E00 "Indian Defence" 1. d4 Nf6 2. c4 e6
+E00 "Catalan Opening" 1. d4 Nf6 2. c4 e6 3. g3
+E10 "Indian Defence" 1. d4 Nf6 2. c4 e6 3. Nf3
+E11 "Bogo-Indian Defence" 1. d4 Nf6 2. c4 e6 3. Nf3 Bb4+
+E12 "Queen's Indian Defence" 1. d4 Nf6 2. c4 e6 3. Nf3 b6
+E20 "Nimzo-Indian Defence" 1. d4 Nf6 2. c4 e6 3. Nc3 Bb4
+E60 "King's Indian Defence" 1. d4 Nf6 2. c4 g6
+E61 "King's Indian Defence" 1. d4 Nf6 2. c4 g6 3. Nc3
+E70 "King's Indian Defence" 1. d4 Nf6 2. c4 g6 3. Nc3 Bg7 4. e4
+"#;
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Opening {
+    pub eco: String,
+    pub name: String,
+}
+
+//Openings keyed by the hash of the position their line reaches, so a game that transposes into a line
+//is classified the same as one that followed it
+#[derive(Clone)]
+pub struct OpeningTable {
+    positions: HashMap<u64, Opening>,
+}
+
+//How a player did in one opening
+#[derive(Clone)]
+pub struct OpeningStats {
+    //? for games that left the table straight away
+    pub eco: String,
+    pub name: String,
+    pub games: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl OpeningTable {
+    //The table built into the engine, built the first time it's asked for
+    pub fn embedded() -> &'static OpeningTable {
+        static TABLE: OnceLock<OpeningTable> = OnceLock::new();
+        return TABLE.get_or_init(|| OpeningTable::parse(OPENINGS).expect("The embedded opening table is invalid"));
+    }
+
+    //Reads one opening per line like B20 "Sicilian Defence" 1. e4 c5, with an optional * at the end.
+    //When two lines reach the same position, the first one names it.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut positions = HashMap::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (eco, name, board) = parse_line(line).map_err(|error| format!("Line {}: {}", number + 1, error))?;
+            positions.entry(board.hash).or_insert(Opening { eco, name });
+        }
+        return Ok(Self { positions });
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        return match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(error) => Err(format!("Failed to read {}: {}", path, error))
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        return self.positions.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.positions.is_empty();
+    }
+
+    //The opening whose line reaches exactly this position
+    pub fn classify(&self, board: &ChessBoard) -> Option<&Opening> {
+        return self.positions.get(&board.hash);
+    }
+
+    //The last position of the main line that's in the table, which is the most specific name the game has
+    pub fn classify_game(&self, game: &Game) -> Option<&Opening> {
+        return (0..=game.len()).rev().find_map(|index| self.classify(game.position(index)?));
+    }
+}
+
+impl ChessBoard {
+    //The opening of the position in the embedded table
+    pub fn opening(&self) -> Option<&'static Opening> {
+        return OpeningTable::embedded().classify(self);
+    }
+}
+
+impl Game {
+    //The opening of the game in the embedded table, for the ECO and Opening PGN tags
+    pub fn opening(&self) -> Option<&'static Opening> {
+        return OpeningTable::embedded().classify_game(self);
+    }
+}
+
+//The player's results in each opening they played, most played first. Results come from the Result tag.
+pub fn results_by_opening(games: &[PgnGame], player: &str, table: &OpeningTable) -> Vec<OpeningStats> {
+    let mut output: Vec<OpeningStats> = Vec::new();
+    for game in games {
+        let team = if game.tag("White") == Some(player) {
+            Teams::White
+        } else if game.tag("Black") == Some(player) {
+            Teams::Black
+        } else {
+            continue;
+        };
+        let (eco, name) = match table.classify_game(&game.game) {
+            Some(opening) => (opening.eco.as_str(), opening.name.as_str()),
+            None => ("?", "Unknown")
+        };
+
+        let index = match output.iter().position(|stats| stats.eco == eco && stats.name == name) {
+            Some(index) => index,
+            None => {
+                output.push(OpeningStats {
+                    eco: String::from(eco),
+                    name: String::from(name),
+                    games: 0,
+                    wins: 0,
+                    draws: 0,
+                    losses: 0,
+                });
+                output.len() - 1
+            }
+        };
+        let stats = &mut output[index];
+        stats.games += 1;
+        match (game.tag("Result"), team) {
+            (Some("1-0"), Teams::White) | (Some("0-1"), Teams::Black) => stats.wins += 1,
+            (Some("1-0"), Teams::Black) | (Some("0-1"), Teams::White) => stats.losses += 1,
+            (Some("1/2-1/2"), _) => stats.draws += 1,
+            _ => {}
+        }
+    }
+    output.sort_by(|first, second| second.games.cmp(&first.games).then_with(|| first.eco.cmp(&second.eco)));
+    return output;
+}
+
+//The code, name and the position the moves reach
+fn parse_line(line: &str) -> Result<(String, String, ChessBoard), String> {
+    let (eco, rest) = line.split_once(char::is_whitespace).ok_or("Expected a code, name and moves")?;
+    let rest = rest.trim_start().strip_prefix('"').ok_or("Expected the name in quotes")?;
+    let (name, moves) = rest.split_once('"').ok_or("Unclosed name")?;
+
+    let mut board = ChessBoard::new();
+    for token in moves.split_whitespace() {
+        //Move numbers like 1. or 1.e4, as scid writes them. The digits are only stripped when a dot follows them,
+        //since 0-0 is castling.
+        let rest = token.trim_start_matches(|character: char| character.is_ascii_digit());
+        let san = if rest.is_empty() || rest.starts_with('.') { rest.trim_start_matches('.') } else { token };
+        if san.is_empty() || san == "*" {
+            continue;
+        }
+        let chess_move = board.parse_san(san).ok_or_else(|| format!("{} isn't legal in {}", san, board.to_fen()))?;
+        board.make_move(&chess_move);
+    }
+    return Ok((String::from(eco), String::from(name), board));
+}
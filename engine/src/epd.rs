@@ -0,0 +1,336 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::time::{Duration, Instant};
+use crate::ChessBoard;
+use crate::search::{MATE_SCORE, Search, SearchLimits};
+use crate::util::Move;
+
+//A position from Extended Position Description, with the operations the test suites use read out
+#[derive(Clone)]
+pub struct Epd {
+    pub board: ChessBoard,
+    //id, the name of the position in its suite
+    pub id: Option<String>,
+    //bm, moves that solve the position
+    pub best_moves: Vec<Move>,
+    //am, moves that fail the position
+    pub avoid_moves: Vec<Move>,
+    //c0, the first comment. STS suites use it to give points for each move.
+    pub comment: Option<String>,
+    //dm, mate in this many moves
+    pub direct_mate: Option<u32>,
+    //ce, evaluation in centipawns for the side to move
+    pub centipawn_eval: Option<i32>,
+    //Every operation as written, with quotes taken off the operands
+    pub operations: Vec<(String, Vec<String>)>,
+    //The first four fields, which are FEN without the clocks
+    position: String,
+}
+
+//Something that picks moves for test positions. The reference search gives its score too,
+//and any closure taking the position and limits can stand in for a bot.
+pub trait Solver {
+    fn solve(&mut self, board: &ChessBoard, limits: SearchLimits) -> (Option<Move>, Option<i32>);
+}
+
+//How the solver did on one position
+#[derive(Clone)]
+pub struct PositionResult {
+    pub id: String,
+    //In algebraic notation
+    pub chosen: Option<String>,
+    pub expected: String,
+    pub solved: bool,
+    //Points scored and available, for suites that give points per move
+    pub points: u32,
+    pub max_points: u32,
+    pub elapsed: Duration,
+}
+
+#[derive(Clone)]
+pub struct SuiteReport {
+    pub results: Vec<PositionResult>,
+}
+
+impl Epd {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let mut fields = Vec::new();
+        let mut rest = line;
+        for _ in 0..4 {
+            rest = rest.trim_start();
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if end == 0 {
+                return Err(format!("Expected 4 position fields in EPD: {}", line));
+            }
+            fields.push(&rest[..end]);
+            rest = &rest[end..];
+        }
+        let position = fields.join(" ");
+        let operations = parse_operations(rest)?;
+
+        //The clocks come from hmvc and fmvn when they're given
+        let operand = |opcode: &str| operations.iter()
+            .find(|(name, _)| name == opcode)
+            .and_then(|(_, operands)| operands.first().cloned());
+        let fen = format!("{} {} {}", position, operand("hmvc").unwrap_or(String::from("0")), operand("fmvn").unwrap_or(String::from("1")));
+        let board = ChessBoard::from_fen(&fen)?;
+
+        let mut epd = Self {
+            board,
+            id: operand("id"),
+            best_moves: Vec::new(),
+            avoid_moves: Vec::new(),
+            comment: operand("c0"),
+            direct_mate: None,
+            centipawn_eval: None,
+            operations: Vec::new(),
+            position,
+        };
+
+        for (opcode, operands) in &operations {
+            match opcode.as_str() {
+                "bm" | "am" => {
+                    for operand in operands {
+                        let chess_move = epd.board.parse_san(operand)
+                            .ok_or_else(|| format!("Move {} in {} isn't legal in {}", operand, opcode, epd.position))?;
+                        if opcode == "bm" {
+                            epd.best_moves.push(chess_move);
+                        } else {
+                            epd.avoid_moves.push(chess_move);
+                        }
+                    }
+                }
+                "dm" => epd.direct_mate = Some(parse_number(opcode, operands)?),
+                "ce" => epd.centipawn_eval = Some(parse_number(opcode, operands)?),
+                _ => {}
+            }
+        }
+        epd.operations = operations;
+        return Ok(epd);
+    }
+
+    //The operands of the first operation with the opcode
+    pub fn operation(&self, opcode: &str) -> Option<&[String]> {
+        return self.operations.iter().find(|(name, _)| name == opcode).map(|(_, operands)| operands.as_slice());
+    }
+
+    //Points for each move from an STS style comment like "f4=10, Be5+=2", or nothing for other comments
+    pub fn move_points(&self) -> Vec<(Move, u32)> {
+        let mut output = Vec::new();
+        let comment = match &self.comment {
+            Some(comment) => comment,
+            None => return output
+        };
+        for item in comment.split(',') {
+            let parsed = item.trim().split_once('=').and_then(|(text, points)| {
+                Some((self.board.parse_san(text.trim())?, points.trim().parse().ok()?))
+            });
+            match parsed {
+                Some(entry) => output.push(entry),
+                None => return Vec::new()
+            }
+        }
+        return output;
+    }
+
+    //Whether the move, and the score the solver gave it if any, answer the position.
+    //Positions with nothing to check can't be solved.
+    pub fn is_solved(&self, chosen: Option<Move>, score: Option<i32>) -> bool {
+        if !self.best_moves.is_empty() || !self.avoid_moves.is_empty() {
+            return match chosen {
+                Some(chosen) => (self.best_moves.is_empty() || self.best_moves.contains(&chosen)) && !self.avoid_moves.contains(&chosen),
+                None => false
+            };
+        }
+        if let Some(moves) = self.direct_mate {
+            return score.map_or(false, |score| score >= MATE_SCORE - (2 * moves as i32 - 1));
+        }
+        return false;
+    }
+}
+
+impl Display for Epd {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.position)?;
+        for (opcode, operands) in &self.operations {
+            write!(f, " {}", opcode)?;
+            //Names and comments are always quoted, other operands only when they have to be
+            let quoted = opcode == "id" || (opcode.len() == 2 && opcode.starts_with('c') && opcode.as_bytes()[1].is_ascii_digit());
+            for operand in operands {
+                if quoted || operand.contains(char::is_whitespace) || operand.contains(';') || operand.is_empty() {
+                    write!(f, " \"{}\"", operand)?;
+                } else {
+                    write!(f, " {}", operand)?;
+                }
+            }
+            write!(f, ";")?;
+        }
+        return Ok(());
+    }
+}
+
+impl Solver for Search {
+    fn solve(&mut self, board: &ChessBoard, limits: SearchLimits) -> (Option<Move>, Option<i32>) {
+        //Each position is searched fresh so results don't depend on the order of the suite
+        self.limits = limits;
+        self.table.clear();
+        self.ordering.clear();
+        let result = self.run(board);
+        return (result.best_move, Some(result.score));
+    }
+}
+
+impl<F: FnMut(&ChessBoard, SearchLimits) -> Option<Move>> Solver for F {
+    fn solve(&mut self, board: &ChessBoard, limits: SearchLimits) -> (Option<Move>, Option<i32>) {
+        return (self(board, limits), None);
+    }
+}
+
+impl SuiteReport {
+    pub fn total(&self) -> usize {
+        return self.results.len();
+    }
+
+    pub fn solved(&self) -> usize {
+        return self.results.iter().filter(|result| result.solved).count();
+    }
+
+    pub fn points(&self) -> u32 {
+        return self.results.iter().map(|result| result.points).sum();
+    }
+
+    pub fn max_points(&self) -> u32 {
+        return self.results.iter().map(|result| result.max_points).sum();
+    }
+}
+
+impl Display for SuiteReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for result in &self.results {
+            write!(f, "{}: {}, played {}, expected {}", result.id, if result.solved { "solved" } else { "unsolved" },
+                result.chosen.as_deref().unwrap_or("nothing"), result.expected)?;
+            if result.max_points > 0 {
+                write!(f, ", {}/{} points", result.points, result.max_points)?;
+            }
+            writeln!(f, " ({:.2}s)", result.elapsed.as_secs_f64())?;
+        }
+        write!(f, "Solved {}/{}", self.solved(), self.total())?;
+        if self.max_points() > 0 {
+            write!(f, ", {}/{} points", self.points(), self.max_points())?;
+        }
+        return Ok(());
+    }
+}
+
+//Reads one EPD per line, skipping blank lines and lines starting with #
+pub fn parse_suite(text: &str) -> Result<Vec<Epd>, String> {
+    let mut output = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        output.push(Epd::parse(line).map_err(|error| format!("Line {}: {}", number + 1, error))?);
+    }
+    return Ok(output);
+}
+
+pub fn load_suite(path: &str) -> Result<Vec<Epd>, String> {
+    return match fs::read_to_string(path) {
+        Ok(text) => parse_suite(&text),
+        Err(error) => Err(format!("Failed to read {}: {}", path, error))
+    };
+}
+
+//Gives the solver every position in the suite under the limits
+pub fn run_suite<S: Solver>(suite: &[Epd], solver: &mut S, limits: SearchLimits) -> SuiteReport {
+    let mut results = Vec::new();
+    for (index, epd) in suite.iter().enumerate() {
+        let start = Instant::now();
+        let (chosen, score) = solver.solve(&epd.board, limits);
+        let elapsed = start.elapsed();
+
+        let move_points = epd.move_points();
+        let points = chosen.and_then(|chosen| move_points.iter().find(|(chess_move, _)| *chess_move == chosen))
+            .map_or(0, |(_, points)| *points);
+
+        let expected = if !epd.best_moves.is_empty() {
+            format!("bm {}", san_list(&epd.board, &epd.best_moves))
+        } else if !epd.avoid_moves.is_empty() {
+            format!("am {}", san_list(&epd.board, &epd.avoid_moves))
+        } else if let Some(moves) = epd.direct_mate {
+            format!("dm {}", moves)
+        } else {
+            String::from("nothing")
+        };
+
+        results.push(PositionResult {
+            id: epd.id.clone().unwrap_or_else(|| format!("#{}", index + 1)),
+            chosen: chosen.map(|chosen| epd.board.to_san(&chosen)),
+            expected,
+            solved: epd.is_solved(chosen, score),
+            points,
+            max_points: move_points.iter().map(|(_, points)| *points).max().unwrap_or(0),
+            elapsed,
+        });
+    }
+    return SuiteReport { results };
+}
+
+fn san_list(board: &ChessBoard, moves: &[Move]) -> String {
+    return moves.iter().map(|chess_move| board.to_san(chess_move)).collect::<Vec<String>>().join(" ");
+}
+
+fn parse_number<T: std::str::FromStr>(opcode: &str, operands: &[String]) -> Result<T, String> {
+    return operands.first()
+        .and_then(|operand| operand.parse().ok())
+        .ok_or_else(|| format!("Expected a number for {}", opcode));
+}
+
+//Splits "bm Qxf7+; id "WAC.001";" into opcodes and operands, keeping quoted operands whole
+fn parse_operations(text: &str) -> Result<Vec<(String, Vec<String>)>, String> {
+    let mut output = Vec::new();
+    let mut tokens: Vec<String> = Vec::new();
+    let mut characters = text.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            ';' => {
+                if !tokens.is_empty() {
+                    let opcode = tokens.remove(0);
+                    output.push((opcode, tokens));
+                    tokens = Vec::new();
+                }
+            }
+            '"' => {
+                let mut token = String::new();
+                loop {
+                    match characters.next() {
+                        Some('"') => break,
+                        Some(character) => token.push(character),
+                        None => return Err(format!("Unclosed quote in EPD operations: {}", text))
+                    }
+                }
+                tokens.push(token);
+            }
+            character if character.is_whitespace() => {}
+            character => {
+                let mut token = String::from(character);
+                while let Some(next) = characters.peek() {
+                    if next.is_whitespace() || *next == ';' {
+                        break;
+                    }
+                    token.push(*next);
+                    characters.next();
+                }
+                tokens.push(token);
+            }
+        }
+    }
+    //The last operation's semicolon is often left off
+    if !tokens.is_empty() {
+        let opcode = tokens.remove(0);
+        output.push((opcode, tokens));
+    }
+    return Ok(output);
+}
@@ -0,0 +1,331 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use crate::ChessBoard;
+use crate::util::Teams;
+
+//Piece square tables are written as white sees the board, with rank 8 on the first line
+const PAWN_MIDDLE_GAME: [i32; 64] = [
+    0, 0, 0, 0, 0, 0, 0, 0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+    5, 5, 10, 25, 25, 10, 5, 5,
+    0, 0, 0, 20, 20, 0, 0, 0,
+    5, -5, -10, 0, 0, -10, -5, 5,
+    5, 10, 10, -20, -20, 10, 10, 5,
+    0, 0, 0, 0, 0, 0, 0, 0
+];
+
+const PAWN_END_GAME: [i32; 64] = [
+    0, 0, 0, 0, 0, 0, 0, 0,
+    80, 80, 80, 80, 80, 80, 80, 80,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    30, 30, 30, 30, 30, 30, 30, 30,
+    15, 15, 15, 15, 15, 15, 15, 15,
+    5, 5, 5, 5, 5, 5, 5, 5,
+    0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0
+];
+
+const KNIGHT_TABLE: [i32; 64] = [
+    -50, -40, -30, -30, -30, -30, -40, -50,
+    -40, -20, 0, 0, 0, 0, -20, -40,
+    -30, 0, 10, 15, 15, 10, 0, -30,
+    -30, 5, 15, 20, 20, 15, 5, -30,
+    -30, 0, 15, 20, 20, 15, 0, -30,
+    -30, 5, 10, 15, 15, 10, 5, -30,
+    -40, -20, 0, 5, 5, 0, -20, -40,
+    -50, -40, -30, -30, -30, -30, -40, -50
+];
+
+const BISHOP_TABLE: [i32; 64] = [
+    -20, -10, -10, -10, -10, -10, -10, -20,
+    -10, 0, 0, 0, 0, 0, 0, -10,
+    -10, 0, 5, 10, 10, 5, 0, -10,
+    -10, 5, 5, 10, 10, 5, 5, -10,
+    -10, 0, 10, 10, 10, 10, 0, -10,
+    -10, 10, 10, 10, 10, 10, 10, -10,
+    -10, 5, 0, 0, 0, 0, 5, -10,
+    -20, -10, -10, -10, -10, -10, -10, -20
+];
+
+const ROOK_TABLE: [i32; 64] = [
+    0, 0, 0, 0, 0, 0, 0, 0,
+    5, 10, 10, 10, 10, 10, 10, 5,
+    -5, 0, 0, 0, 0, 0, 0, -5,
+    -5, 0, 0, 0, 0, 0, 0, -5,
+    -5, 0, 0, 0, 0, 0, 0, -5,
+    -5, 0, 0, 0, 0, 0, 0, -5,
+    -5, 0, 0, 0, 0, 0, 0, -5,
+    0, 0, 0, 5, 5, 0, 0, 0
+];
+
+const QUEEN_TABLE: [i32; 64] = [
+    -20, -10, -10, -5, -5, -10, -10, -20,
+    -10, 0, 0, 0, 0, 0, 0, -10,
+    -10, 0, 5, 5, 5, 5, 0, -10,
+    -5, 0, 5, 5, 5, 5, 0, -5,
+    0, 0, 5, 5, 5, 5, 0, -5,
+    -10, 5, 5, 5, 5, 5, 0, -10,
+    -10, 0, 5, 0, 0, 0, 0, -10,
+    -20, -10, -10, -5, -5, -10, -10, -20
+];
+
+const KING_MIDDLE_GAME: [i32; 64] = [
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -20, -30, -30, -40, -40, -30, -30, -20,
+    -10, -20, -20, -20, -20, -20, -20, -10,
+    20, 20, 0, 0, 0, 0, 20, 20,
+    20, 30, 10, 0, 0, 10, 30, 20
+];
+
+const KING_END_GAME: [i32; 64] = [
+    -50, -40, -30, -20, -20, -30, -40, -50,
+    -30, -20, -10, 0, 0, -10, -20, -30,
+    -30, -10, 20, 30, 30, 20, -10, -30,
+    -30, -10, 30, 40, 40, 30, -10, -30,
+    -30, -10, 30, 40, 40, 30, -10, -30,
+    -30, -10, 20, 30, 30, 20, -10, -30,
+    -30, -30, 0, 0, 0, 0, -30, -30,
+    -50, -30, -30, -30, -30, -30, -30, -50
+];
+
+//How many of each piece a team starts with, for the phase of the starting position
+const STARTING_COUNTS: [i32; 6] = [8, 2, 2, 2, 1, 1];
+
+const PIECE_NAMES: [&str; 6] = ["pawn", "knight", "bishop", "rook", "queen", "king"];
+
+pub const DEFAULT_WEIGHTS: EvalWeights = EvalWeights {
+    material_middle_game: [82, 337, 365, 477, 1025, 0],
+    material_end_game: [94, 281, 297, 512, 936, 0],
+    middle_game_tables: [PAWN_MIDDLE_GAME, KNIGHT_TABLE, BISHOP_TABLE, ROOK_TABLE, QUEEN_TABLE, KING_MIDDLE_GAME],
+    end_game_tables: [PAWN_END_GAME, KNIGHT_TABLE, BISHOP_TABLE, ROOK_TABLE, QUEEN_TABLE, KING_END_GAME],
+    phase: [0, 1, 1, 2, 4, 0],
+};
+
+//Everything the evaluator scores with, indexed by Pieces
+#[derive(Clone)]
+pub struct EvalWeights {
+    pub material_middle_game: [i32; 6],
+    pub material_end_game: [i32; 6],
+    pub middle_game_tables: [[i32; 64]; 6],
+    pub end_game_tables: [[i32; 64]; 6],
+    //How much each remaining piece pushes the game towards the middle game
+    pub phase: [i32; 6],
+}
+
+//A middle game and end game score pair, from white's point of view
+#[derive(Clone, Copy)]
+pub struct Score {
+    pub middle_game: i32,
+    pub end_game: i32,
+}
+
+//An evaluation split by term
+#[derive(Clone, Copy)]
+pub struct Evaluation {
+    pub material: Score,
+    pub piece_squares: Score,
+    //Remaining phase material, from 0 for bare kings up to max_phase for the starting position
+    pub phase: i32,
+    pub max_phase: i32,
+}
+
+impl EvalWeights {
+    pub fn new() -> Self {
+        return DEFAULT_WEIGHTS.clone();
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        return match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(error) => Err(format!("Failed to read {}: {}", path, error))
+        };
+    }
+
+    //Reads weights written as "key = values" lines, with table values allowed to continue on the following lines.
+    //Keys that aren't in the text keep their default values.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut weights = Self::new();
+        let mut entries: Vec<(String, Vec<i32>)> = Vec::new();
+
+        for line in text.lines() {
+            let line = match line.find('#') {
+                Some(comment) => &line[..comment],
+                None => line
+            }.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let values = match line.find('=') {
+                Some(split) => {
+                    entries.push((line[..split].trim().to_string(), Vec::new()));
+                    &line[split + 1..]
+                }
+                None => line
+            };
+
+            let entry = match entries.last_mut() {
+                Some(entry) => entry,
+                None => return Err(format!("Values before any key: {}", line))
+            };
+            for value in values.split_whitespace() {
+                match value.trim_end_matches(',').parse::<i32>() {
+                    Ok(value) => entry.1.push(value),
+                    Err(_) => return Err(format!("Invalid value for {}: {}", entry.0, value))
+                }
+            }
+        }
+
+        for (key, values) in entries {
+            let target: &mut [i32] = match key.as_str() {
+                "material_middle_game" => &mut weights.material_middle_game,
+                "material_end_game" => &mut weights.material_end_game,
+                "phase" => &mut weights.phase,
+                _ => match key.strip_suffix("_middle_game").or(key.strip_suffix("_end_game"))
+                    .and_then(|piece| PIECE_NAMES.iter().position(|name| *name == piece)) {
+                    Some(piece) => if key.ends_with("_middle_game") {
+                        &mut weights.middle_game_tables[piece]
+                    } else {
+                        &mut weights.end_game_tables[piece]
+                    }
+                    None => return Err(format!("Unknown weight: {}", key))
+                }
+            };
+
+            if values.len() != target.len() {
+                return Err(format!("{} needs {} values, found {}", key, target.len(), values.len()));
+            }
+            target.copy_from_slice(&values);
+        }
+        return Ok(weights);
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        return fs::write(path, self.to_string()).map_err(|error| format!("Failed to write {}: {}", path, error));
+    }
+
+    pub fn max_phase(&self) -> i32 {
+        let mut output = 0;
+        for piece in 0..6 {
+            output += self.phase[piece] * STARTING_COUNTS[piece] * 2;
+        }
+        return output;
+    }
+
+    //Remaining phase material on the board, capped at max_phase when promotions add pieces
+    pub fn game_phase(&self, board: &ChessBoard) -> i32 {
+        let mut output = 0;
+        for piece in 0..6 {
+            output += self.phase[piece] * (board.board.0[piece].count_ones() as i32 +
+                board.board.0[piece + Teams::Black as usize].count_ones() as i32);
+        }
+        return output.min(self.max_phase());
+    }
+
+    pub fn evaluate(&self, board: &ChessBoard) -> Evaluation {
+        let mut material = Score { middle_game: 0, end_game: 0 };
+        let mut piece_squares = Score { middle_game: 0, end_game: 0 };
+
+        for team in [Teams::White, Teams::Black] {
+            let sign = match team {
+                Teams::White => 1,
+                Teams::Black => -1
+            };
+            //Tables are written rank 8 first, so white flips the rank and black reads its own view directly
+            let flip = match team {
+                Teams::White => 56,
+                Teams::Black => 0
+            };
+
+            for piece in 0..6 {
+                let mut remaining = board.board.0[piece + team as usize];
+                while remaining != 0 {
+                    let square = remaining.trailing_zeros() as usize ^ flip;
+                    remaining &= remaining - 1;
+
+                    material.middle_game += sign * self.material_middle_game[piece];
+                    material.end_game += sign * self.material_end_game[piece];
+                    piece_squares.middle_game += sign * self.middle_game_tables[piece][square];
+                    piece_squares.end_game += sign * self.end_game_tables[piece][square];
+                }
+            }
+        }
+
+        return Evaluation {
+            material,
+            piece_squares,
+            phase: self.game_phase(board),
+            max_phase: self.max_phase(),
+        };
+    }
+}
+
+impl Display for EvalWeights {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let write_values = |f: &mut Formatter<'_>, key: &str, values: &[i32]| -> std::fmt::Result {
+            write!(f, "{} =", key)?;
+            for (i, value) in values.iter().enumerate() {
+                if values.len() == 64 && i % 8 == 0 {
+                    write!(f, "\n   ")?;
+                }
+                write!(f, " {}", value)?;
+            }
+            return writeln!(f);
+        };
+
+        write_values(f, "material_middle_game", &self.material_middle_game)?;
+        write_values(f, "material_end_game", &self.material_end_game)?;
+        write_values(f, "phase", &self.phase)?;
+        for piece in 0..6 {
+            write_values(f, &format!("{}_middle_game", PIECE_NAMES[piece]), &self.middle_game_tables[piece])?;
+            write_values(f, &format!("{}_end_game", PIECE_NAMES[piece]), &self.end_game_tables[piece])?;
+        }
+        return Ok(());
+    }
+}
+
+impl Score {
+    #[inline]
+    pub fn taper(&self, phase: i32, max_phase: i32) -> i32 {
+        if max_phase == 0 {
+            return self.end_game;
+        }
+        return (self.middle_game * phase + self.end_game * (max_phase - phase)) / max_phase;
+    }
+}
+
+impl Evaluation {
+    //The tapered total from white's point of view
+    pub fn total(&self) -> i32 {
+        return self.material.taper(self.phase, self.max_phase) + self.piece_squares.taper(self.phase, self.max_phase);
+    }
+
+    //The tapered total from the team's point of view, as negamax searches expect
+    pub fn relative(&self, team: Teams) -> i32 {
+        return match team {
+            Teams::White => self.total(),
+            Teams::Black => -self.total()
+        };
+    }
+}
+
+impl Display for Evaluation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Term          Middle    End  Tapered")?;
+        writeln!(f, "Material      {:>6} {:>6} {:>8}", self.material.middle_game, self.material.end_game,
+                 self.material.taper(self.phase, self.max_phase))?;
+        writeln!(f, "Piece squares {:>6} {:>6} {:>8}", self.piece_squares.middle_game, self.piece_squares.end_game,
+                 self.piece_squares.taper(self.phase, self.max_phase))?;
+        writeln!(f, "Phase         {}/{}", self.phase, self.max_phase)?;
+        return write!(f, "Total         {:>22}", self.total());
+    }
+}
+
+//Evaluates the board with the default weights, from the side to move's point of view
+pub fn evaluate(board: &ChessBoard) -> i32 {
+    return DEFAULT_WEIGHTS.evaluate(board).relative(board.side_to_move());
+}
@@ -0,0 +1,162 @@
+use crate::ChessBoard;
+use crate::bitboard::{File, Rank, Square};
+use crate::pieces::Pieces;
+use crate::util::{Board, GameStatus, Teams};
+use crate::validate::MAX_HALFMOVE_CLOCK;
+
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+impl ChessBoard {
+    //Reads a position from Forsyth-Edwards Notation. The clocks can be left off, as they are in EPD.
+    pub fn from_fen(fen: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 {
+            return Err(format!("Expected at least 4 fields in FEN, got {}", fields.len()));
+        }
+
+        let mut board = Board([0; 13]);
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(format!("Expected 8 ranks in FEN, got {}", ranks.len()));
+        }
+        for (row, rank) in ranks.iter().enumerate() {
+            let mut file = 0;
+            for character in rank.chars() {
+                if let Some(empty) = character.to_digit(10) {
+                    file += empty as usize;
+                    continue;
+                }
+                let (piece, team) = Pieces::from_letter(character)
+                    .ok_or_else(|| format!("Unknown piece {} in FEN", character))?;
+                if file > 7 {
+                    return Err(format!("Too many squares in rank {}", 8 - row));
+                }
+                let square = 1u64 << ((7 - row) * 8 + file);
+                board.0[piece as usize + team as usize] |= square;
+                board.0[12] |= square;
+                file += 1;
+            }
+            if file != 8 {
+                return Err(format!("Rank {} has {} squares instead of 8", 8 - row, file));
+            }
+        }
+
+        let team = match fields[1] {
+            "w" => Teams::White,
+            "b" => Teams::Black,
+            side => return Err(format!("Unknown side to move {}", side))
+        };
+
+        //Indexed like castle_status: white queenside, white kingside, black queenside, black kingside
+        let mut castle_status = [false; 4];
+        if fields[2] != "-" {
+            for character in fields[2].chars() {
+                let index = match character {
+                    'Q' => 0,
+                    'K' => 1,
+                    'q' => 2,
+                    'k' => 3,
+                    _ => return Err(format!("Unknown castling right {}", character))
+                };
+                castle_status[index] = true;
+            }
+        }
+
+        //FEN gives the square behind the pawn, but the board keeps the pawn itself. Validation checks it's really there.
+        let mut last_pawn = None;
+        if fields[3] != "-" {
+            let (rank, direction) = match team {
+                Teams::White => (Rank(5), -1),
+                Teams::Black => (Rank(2), 1)
+            };
+            let pawn = Square::parse(fields[3]).filter(|target| target.rank() == rank).and_then(|target| target.offset(0, direction));
+            last_pawn = Some(pawn.ok_or_else(|| format!("Bad en passant square {}", fields[3]))?);
+        }
+
+        let halfmoves: u8 = match fields.get(4) {
+            Some(field) => field.parse().ok().filter(|halfmoves| *halfmoves <= MAX_HALFMOVE_CLOCK)
+                .ok_or_else(|| format!("Bad halfmove clock {}", field))?,
+            None => 0
+        };
+        let fullmoves: u16 = match fields.get(5) {
+            Some(field) => field.parse().map_err(|_| format!("Bad fullmove number {}", field))?,
+            None => 1
+        };
+
+        //Plies since the start, which has to fit even for made up move numbers
+        let move_number = (fullmoves.max(1) - 1).checked_mul(2).and_then(|plies| plies.checked_add(team.index() as u16))
+            .ok_or_else(|| format!("Fullmove number {} is too large", fullmoves))?;
+
+        let mut output = Self {
+            board,
+            move_number,
+            fifty_move_counter: 100u8.saturating_sub(halfmoves),
+            castle_status,
+            last_pawn,
+            game_status: GameStatus::Ongoing,
+            history: Vec::new(),
+            hash: 0,
+        };
+        let problems = output.validate();
+        if !problems.is_empty() {
+            return Err(format!("Invalid position in FEN: {}",
+                problems.iter().map(|problem| problem.to_string()).collect::<Vec<String>>().join(", ")));
+        }
+        output.hash = output.compute_hash();
+        output.history.push(output.hash);
+        return Ok(output);
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut output = String::new();
+        for row in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                match self.piece_at(Square::new(File(file), Rank(row))) {
+                    Some((piece, team)) => {
+                        if empty > 0 {
+                            output.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        output.push(piece.letter(team));
+                    }
+                    None => empty += 1
+                }
+            }
+            if empty > 0 {
+                output.push_str(&empty.to_string());
+            }
+            if row > 0 {
+                output.push('/');
+            }
+        }
+
+        let team = self.side_to_move();
+        output.push_str(match team {
+            Teams::White => " w ",
+            Teams::Black => " b "
+        });
+
+        let mut castling: String = ['K', 'Q', 'k', 'q'].iter().zip([1, 0, 3, 2])
+            .filter(|(_, index)| self.castle_status[*index])
+            .map(|(character, _)| *character)
+            .collect();
+        if castling.is_empty() {
+            castling.push('-');
+        }
+        output.push_str(&castling);
+
+        output.push(' ');
+        let target = self.last_pawn.and_then(|pawn| pawn.offset(0, match team {
+            Teams::White => 1,
+            Teams::Black => -1
+        }));
+        match target {
+            Some(target) => output.push_str(&target.to_string()),
+            None => output.push('-')
+        }
+
+        output.push_str(&format!(" {} {}", 100 - self.fifty_move_counter.min(100), self.move_number / 2 + 1));
+        return output;
+    }
+}
@@ -0,0 +1,503 @@
+use std::time::SystemTime;
+use crate::ChessBoard;
+use crate::fen::STARTING_FEN;
+use crate::util::{GameResult, GameStatus, Move, Teams, Termination};
+
+//A move as it was played in a game
+#[derive(Clone)]
+pub struct GameMove {
+    pub chess_move: Move,
+    //Standard algebraic notation, worked out when the move is played
+    pub san: String,
+    pub time: SystemTime,
+    pub comment: Option<String>,
+    //Other lines that could have been played instead of this move, each starting from the same position
+    pub variations: Vec<Vec<GameMove>>,
+}
+
+//Something a player does in a game other than moving
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum GameAction {
+    OfferDraw,
+    AcceptDraw,
+    DeclineDraw,
+    ClaimDraw,
+    Resign,
+}
+
+#[derive(Clone, Copy)]
+pub struct GameEvent {
+    pub team: Teams,
+    pub action: GameAction,
+    //How many main line moves had been played when it happened
+    pub ply: usize,
+    pub time: SystemTime,
+}
+
+//A game from its starting position, with every position along the main line kept so it can be stepped through
+#[derive(Clone)]
+pub struct Game {
+    pub started: SystemTime,
+    moves: Vec<GameMove>,
+    //positions[i] is the position after the first i moves
+    positions: Vec<ChessBoard>,
+    //Index into positions of the position being looked at
+    current: usize,
+    //Set when the game ends for a reason the board can't see, like a resignation or a flag falling
+    result: Option<GameResult>,
+    //The halfmove clock of the starting position, which can be past where the board's counter stops
+    start_halfmoves: u32,
+    //Draw offers, claims and resignations, in order
+    events: Vec<GameEvent>,
+    //The team with a draw offer waiting for an answer
+    draw_offer: Option<Teams>,
+}
+
+impl Game {
+    pub fn new() -> Self {
+        return Self::from_position(ChessBoard::new());
+    }
+
+    pub fn from_position(board: ChessBoard) -> Self {
+        let start_halfmoves = 100 - board.fifty_move_counter.min(100) as u32;
+        return Self {
+            started: SystemTime::now(),
+            moves: Vec::new(),
+            positions: vec![board],
+            current: 0,
+            result: None,
+            start_halfmoves,
+            events: Vec::new(),
+            draw_offer: None,
+        };
+    }
+
+    //Starts from a position whose halfmove clock is known past the 100 the board's counter stops at
+    pub fn with_halfmove_clock(board: ChessBoard, halfmoves: u32) -> Self {
+        let mut game = Self::from_position(board);
+        game.start_halfmoves = halfmoves;
+        return game;
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Self, String> {
+        let board = ChessBoard::from_fen(fen)?;
+        return Ok(match fen.split_whitespace().nth(4).and_then(|field| field.parse().ok()) {
+            Some(halfmoves) => Self::with_halfmove_clock(board, halfmoves),
+            None => Self::from_position(board)
+        });
+    }
+
+    pub fn start(&self) -> &ChessBoard {
+        return &self.positions[0];
+    }
+
+    //The main line
+    pub fn moves(&self) -> &[GameMove] {
+        return &self.moves;
+    }
+
+    pub fn len(&self) -> usize {
+        return self.moves.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.moves.is_empty();
+    }
+
+    //The position after the first index moves of the main line
+    pub fn position(&self, index: usize) -> Option<&ChessBoard> {
+        return self.positions.get(index);
+    }
+
+    pub fn current(&self) -> &ChessBoard {
+        return &self.positions[self.current];
+    }
+
+    //How many moves into the main line the current position is
+    pub fn current_index(&self) -> usize {
+        return self.current;
+    }
+
+    pub fn last_position(&self) -> &ChessBoard {
+        return self.positions.last().unwrap();
+    }
+
+    pub fn at_end(&self) -> bool {
+        return self.current == self.moves.len();
+    }
+
+    //Plays a legal move from the current position. Moves that came after the current position
+    //are kept as a variation of the new move, unless the new move is the same as the next one.
+    pub fn play(&mut self, chess_move: &Move) -> Result<(), String> {
+        if self.result.is_some() || (self.at_end() && self.status().is_over()) {
+            return Err(format!("The game is over: {}", self.status()));
+        }
+        let board = self.current();
+        if !board.legal_moves().contains(chess_move) {
+            return Err(format!("{} isn't legal in {}", chess_move, board.to_fen()));
+        }
+
+        if let Some(next) = self.moves.get(self.current) {
+            if next.chess_move == *chess_move {
+                self.current += 1;
+                return Ok(());
+            }
+        }
+
+        let mut played = GameMove {
+            chess_move: *chess_move,
+            san: board.to_san(chess_move),
+            time: SystemTime::now(),
+            comment: None,
+            variations: Vec::new(),
+        };
+        let mut next = board.clone();
+        next.make_move(chess_move);
+
+        if !self.at_end() {
+            let mut rest = self.moves.split_off(self.current);
+            played.variations = rest[0].variations.drain(..).collect();
+            played.variations.insert(0, rest);
+            self.positions.truncate(self.current + 1);
+        }
+        //Moving instead of answering a draw offer turns it down
+        if self.draw_offer == Some(!chess_move.team) {
+            self.draw_offer = None;
+        }
+        self.moves.push(played);
+        self.positions.push(next);
+        self.current += 1;
+        return Ok(());
+    }
+
+    pub fn events(&self) -> &[GameEvent] {
+        return &self.events;
+    }
+
+    pub fn draw_offer(&self) -> Option<Teams> {
+        return self.draw_offer;
+    }
+
+    //Offers a draw, which stands until the other side answers it or makes a move
+    pub fn offer_draw(&mut self, team: Teams) -> Result<(), String> {
+        self.check_ongoing()?;
+        if self.draw_offer.is_some() {
+            return Err(format!("{} already has a draw offer waiting", self.draw_offer.unwrap()));
+        }
+        self.draw_offer = Some(team);
+        self.record(team, GameAction::OfferDraw);
+        return Ok(());
+    }
+
+    pub fn accept_draw(&mut self, team: Teams) -> Result<(), String> {
+        self.check_ongoing()?;
+        if self.draw_offer != Some(!team) {
+            return Err(format!("{} has no draw offer to accept", team));
+        }
+        self.draw_offer = None;
+        self.record(team, GameAction::AcceptDraw);
+        return self.finish(GameResult { winner: None, termination: Termination::AgreedDraw });
+    }
+
+    pub fn decline_draw(&mut self, team: Teams) -> Result<(), String> {
+        self.check_ongoing()?;
+        if self.draw_offer != Some(!team) {
+            return Err(format!("{} has no draw offer to decline", team));
+        }
+        self.draw_offer = None;
+        self.record(team, GameAction::DeclineDraw);
+        return Ok(());
+    }
+
+    //Claims a draw by threefold repetition or the fifty move rule. The side to move can claim on the
+    //position now, or on the position after a move it's about to play, which is then played.
+    pub fn claim_draw(&mut self, team: Teams, chess_move: Option<&Move>) -> Result<(), String> {
+        self.check_ongoing()?;
+        let board = self.last_position();
+        if board.side_to_move() != team {
+            return Err(format!("{} can only claim a draw on their own turn", team));
+        }
+
+        let mut claimed = board.clone();
+        let mut halfmoves = self.halfmove_clock();
+        if let Some(chess_move) = chess_move {
+            if !board.legal_moves().contains(chess_move) {
+                return Err(format!("{} isn't legal in {}", chess_move, board.to_fen()));
+            }
+            claimed.make_move(chess_move);
+            halfmoves = if claimed.fifty_move_counter == 100 { 0 } else { halfmoves + 1 };
+        }
+
+        let termination = if claimed.repetitions() >= 3 {
+            Termination::Repetition
+        } else if halfmoves >= 100 {
+            Termination::FiftyMoveRule
+        } else {
+            return Err(String::from("Neither threefold repetition nor the fifty move rule applies"));
+        };
+
+        self.current = self.moves.len();
+        if let Some(chess_move) = chess_move {
+            self.play(chess_move)?;
+            //A move that mates or stalemates ends the game before the claim matters
+            if self.status().is_over() {
+                return Ok(());
+            }
+        }
+        self.draw_offer = None;
+        self.record(team, GameAction::ClaimDraw);
+        return self.finish(GameResult { winner: None, termination });
+    }
+
+    pub fn resign(&mut self, team: Teams) -> Result<(), String> {
+        self.check_ongoing()?;
+        self.draw_offer = None;
+        self.record(team, GameAction::Resign);
+        return self.finish(GameResult { winner: Some(!team), termination: Termination::Resignation });
+    }
+
+    fn check_ongoing(&self) -> Result<(), String> {
+        let status = self.status();
+        if status.is_over() {
+            return Err(format!("The game is over: {}", status));
+        }
+        return Ok(());
+    }
+
+    fn record(&mut self, team: Teams, action: GameAction) {
+        self.events.push(GameEvent {
+            team,
+            action,
+            ply: self.moves.len(),
+            time: SystemTime::now(),
+        });
+    }
+
+    //Plays a move in algebraic or coordinate notation
+    pub fn play_san(&mut self, text: &str) -> Result<(), String> {
+        let chess_move = self.current().parse_san(text)
+            .ok_or_else(|| format!("{} isn't a legal move in {}", text, self.current().to_fen()))?;
+        return self.play(&chess_move);
+    }
+
+    pub fn back(&mut self) -> bool {
+        if self.current == 0 {
+            return false;
+        }
+        self.current -= 1;
+        return true;
+    }
+
+    pub fn forward(&mut self) -> bool {
+        if self.at_end() {
+            return false;
+        }
+        self.current += 1;
+        return true;
+    }
+
+    pub fn go_to(&mut self, index: usize) -> bool {
+        if index > self.moves.len() {
+            return false;
+        }
+        self.current = index;
+        return true;
+    }
+
+    //Drops every main line move after the first len
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.moves.len() {
+            self.result = None;
+            self.draw_offer = None;
+            self.events.retain(|event| event.ply <= len);
+        }
+        self.moves.truncate(len);
+        self.positions.truncate(self.moves.len() + 1);
+        self.current = self.current.min(self.moves.len());
+    }
+
+    pub fn set_comment(&mut self, index: usize, comment: Option<String>) -> bool {
+        return match self.moves.get_mut(index) {
+            Some(game_move) => {
+                game_move.comment = comment;
+                true
+            }
+            None => false
+        };
+    }
+
+    //Adds a line that could have been played instead of the main line move at index, returning its number
+    pub fn add_variation(&mut self, index: usize, moves: &[Move]) -> Result<usize, String> {
+        if index >= self.moves.len() {
+            return Err(format!("There is no move {} to add a variation to", index));
+        }
+
+        let mut board = self.positions[index].clone();
+        let mut line = Vec::new();
+        for chess_move in moves {
+            if !board.legal_moves().contains(chess_move) {
+                return Err(format!("{} isn't legal in {}", chess_move, board.to_fen()));
+            }
+            line.push(GameMove {
+                chess_move: *chess_move,
+                san: board.to_san(chess_move),
+                time: SystemTime::now(),
+                comment: None,
+                variations: Vec::new(),
+            });
+            board.make_move(chess_move);
+        }
+        if line.is_empty() {
+            return Err(String::from("A variation needs at least one move"));
+        }
+
+        let variations = &mut self.moves[index].variations;
+        variations.push(line);
+        return Ok(variations.len() - 1);
+    }
+
+    //Swaps a variation into the main line, and the main line from that move on becomes the variation
+    pub fn promote_variation(&mut self, index: usize, variation: usize) -> bool {
+        if index >= self.moves.len() || variation >= self.moves[index].variations.len() {
+            return false;
+        }
+
+        let mut line = self.moves[index].variations.remove(variation);
+        let mut rest = self.moves.split_off(index);
+        let mut others: Vec<Vec<GameMove>> = rest[0].variations.drain(..).collect();
+        others.insert(variation, rest);
+        line[0].variations = others;
+
+        self.positions.truncate(index + 1);
+        for game_move in &line {
+            let mut next = self.positions.last().unwrap().clone();
+            next.make_move(&game_move.chess_move);
+            self.positions.push(next);
+        }
+        self.moves.extend(line);
+        self.current = self.current.min(index);
+        self.result = None;
+        return true;
+    }
+
+    //A new game following the main line up to index, to explore from there without changing this one
+    pub fn branch(&self, index: usize) -> Game {
+        let mut game = self.clone();
+        game.truncate(index);
+        game.current = game.moves.len();
+        return game;
+    }
+
+    //How the game stands at the end of the main line. Checkmate, stalemate, dead positions, fivefold repetition
+    //and the seventy-five move rule end it automatically, and anything else has to be given to finish.
+    pub fn status(&self) -> GameStatus {
+        if let Some(result) = self.result {
+            return GameStatus::Finished(result);
+        }
+        let board = self.last_position();
+        let status = board.status();
+        if status.is_over() {
+            return status;
+        }
+        if board.repetitions() >= 5 {
+            return GameStatus::draw(Termination::Repetition);
+        }
+        if self.halfmove_clock() >= 150 {
+            return GameStatus::draw(Termination::SeventyFiveMoveRule);
+        }
+        return GameStatus::Ongoing;
+    }
+
+    //Ends the game with a result from outside the board, like a resignation, time forfeit or adjudication
+    pub fn finish(&mut self, result: GameResult) -> Result<(), String> {
+        let status = self.status();
+        if status.is_over() {
+            return Err(format!("The game is already over: {}", status));
+        }
+        self.result = Some(result);
+        return Ok(());
+    }
+
+    //Plies since the last capture or pawn move at the end of the main line. Unlike the board's counter
+    //this doesn't stop at 100, so the seventy-five move rule can be seen.
+    pub fn halfmove_clock(&self) -> u32 {
+        let mut clock = 0;
+        for index in (0..self.moves.len()).rev() {
+            //The board's counter goes back to 100 on captures and pawn moves
+            if self.positions[index + 1].fifty_move_counter == 100 {
+                return clock;
+            }
+            clock += 1;
+        }
+        return clock + self.start_halfmoves;
+    }
+
+    //The game as PGN, with the Result and Termination tags, the ECO and Opening tags of the opening unless an ECO tag
+    //is given, and the starting position added to the given tags
+    pub fn to_pgn(&self, tags: &[(&str, &str)]) -> String {
+        let status = self.status();
+        let mut output = String::new();
+        for (name, value) in tags {
+            output.push_str(&format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\"")));
+        }
+        output.push_str(&format!("[Result \"{}\"]\n", status.pgn_result()));
+        output.push_str(&format!("[Termination \"{}\"]\n", status.pgn_termination()));
+        if !tags.iter().any(|(name, _)| *name == "ECO") {
+            if let Some(opening) = self.opening() {
+                output.push_str(&format!("[ECO \"{}\"]\n[Opening \"{}\"]\n", opening.eco, opening.name.replace('\\', "\\\\").replace('"', "\\\"")));
+            }
+        }
+        let start = self.start().to_fen();
+        if start != STARTING_FEN {
+            output.push_str(&format!("[SetUp \"1\"]\n[FEN \"{}\"]\n", start));
+        }
+
+        output.push('\n');
+        let movetext = self.movetext();
+        if !movetext.is_empty() {
+            output.push_str(&movetext);
+            output.push(' ');
+        }
+        output.push_str(status.pgn_result());
+        output.push('\n');
+        return output;
+    }
+
+    //Move text in PGN style, with comments and variations
+    pub fn movetext(&self) -> String {
+        let mut output = String::new();
+        write_line(&mut output, &self.moves, self.start().move_number);
+        return output;
+    }
+}
+
+fn write_line(output: &mut String, moves: &[GameMove], move_number: u16) {
+    let mut move_number = move_number;
+    //Black's move needs its number written when it starts a line or follows a comment or variation
+    let mut needs_number = true;
+    for game_move in moves {
+        if !output.is_empty() && !output.ends_with('(') {
+            output.push(' ');
+        }
+        match Teams::from(move_number) {
+            Teams::White => output.push_str(&format!("{}. ", move_number / 2 + 1)),
+            Teams::Black => if needs_number {
+                output.push_str(&format!("{}... ", move_number / 2 + 1));
+            }
+        }
+        output.push_str(&game_move.san);
+        needs_number = false;
+
+        if let Some(comment) = &game_move.comment {
+            output.push_str(&format!(" {{{}}}", comment));
+            needs_number = true;
+        }
+        for variation in &game_move.variations {
+            output.push_str(" (");
+            write_line(output, variation, move_number);
+            output.push(')');
+            needs_number = true;
+        }
+        move_number += 1;
+    }
+}
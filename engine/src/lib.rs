@@ -1,31 +1,58 @@
-use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
+use std::mem;
 use std::ops::{Not, Range};
 use crate::pieces::{BOTTOM_ROW, FIRST_SQUARE, Pieces, TOP_ROW};
-use crate::util::{Board, Directions, GameStatus, Position, Teams};
+use crate::bitboard::{Bitboard, Square};
+use crate::movegen::{CASTLE_ROOKS, KING_STARTS};
+use crate::util::{Board, Directions, GameStatus, Move, Position, Teams, Termination};
+use crate::zobrist::{castle_key, piece_key, side_key};
 
 pub mod attacks;
+pub mod bitboard;
+pub mod book;
+pub mod builder;
+pub mod compact;
+pub mod eco;
+pub mod epd;
+pub mod eval;
+pub mod fen;
+pub mod game;
+pub mod mate;
+pub mod movegen;
+pub mod movelist;
 pub mod notation;
+pub mod ordering;
+pub mod pawns;
+pub mod pgn;
 pub mod pieces;
+pub mod puzzle;
+pub mod search;
+pub mod see;
+pub mod syzygy;
+pub mod tactics;
+pub mod time;
+pub mod transposition;
 pub mod util;
+pub mod validate;
+pub mod zobrist;
 
 const STARTING_BOARD: [u64; 6 * 2 + 1] = [
-    0b00000000_11111111_00000000_00000000_00000000_00000000_00000000_00000000u64, //Black Pawns
-    0b01000010_00000000_00000000_00000000_00000000_00000000_00000000_00000000u64, //Black Knights
-    0b00100100_00000000_00000000_00000000_00000000_00000000_00000000_00000000u64, //Black Bishops
-    0b10000001_00000000_00000000_00000000_00000000_00000000_00000000_00000000u64, //Black Rooks
-    0b00010000_00000000_00000000_00000000_00000000_00000000_00000000_00000000u64, //Black King
-    0b00001000_00000000_00000000_00000000_00000000_00000000_00000000_00000000u64, //Black Queen
     0b00000000_00000000_00000000_00000000_00000000_00000000_11111111_00000000u64, //White Pawns
     0b00000000_00000000_00000000_00000000_00000000_00000000_00000000_01000010u64, //White Knights
     0b00000000_00000000_00000000_00000000_00000000_00000000_00000000_00100100u64, //White Bishops
     0b00000000_00000000_00000000_00000000_00000000_00000000_00000000_10000001u64, //White Rooks
-    0b00000000_00000000_00000000_00000000_00000000_00000000_00000000_00010000u64, //White King
     0b00000000_00000000_00000000_00000000_00000000_00000000_00000000_00001000u64, //White Queen
+    0b00000000_00000000_00000000_00000000_00000000_00000000_00000000_00010000u64, //White King
+    0b00000000_11111111_00000000_00000000_00000000_00000000_00000000_00000000u64, //Black Pawns
+    0b01000010_00000000_00000000_00000000_00000000_00000000_00000000_00000000u64, //Black Knights
+    0b00100100_00000000_00000000_00000000_00000000_00000000_00000000_00000000u64, //Black Bishops
+    0b10000001_00000000_00000000_00000000_00000000_00000000_00000000_00000000u64, //Black Rooks
+    0b00001000_00000000_00000000_00000000_00000000_00000000_00000000_00000000u64, //Black Queen
+    0b00010000_00000000_00000000_00000000_00000000_00000000_00000000_00000000u64, //Black King
     0xFF_FF_00_00_00_00_FF_FF //All pieces
 ];
 
-pub const PIECES: [char; 12] = ['♟', '♞', '♝', '♜', '♛', '♚', '♙', '♘', '♗', '♖', '♕', '♔'];
+pub const PIECES: [char; 12] = ['♙', '♘', '♗', '♖', '♕', '♔', '♟', '♞', '♝', '♜', '♛', '♚'];
 
 #[derive(Clone)]
 pub struct ChessBoard {
@@ -37,24 +64,37 @@ pub struct ChessBoard {
     //Whether different sides are disqualified from castling. Still may not be possible due to check.
     pub castle_status: [bool; 4],
     //Location of the last moved pawn for en passant
-    pub last_pawn: Option<Position>,
+    pub last_pawn: Option<Square>,
     //Status of the game
     pub game_status: GameStatus,
-    //Last board states since significant move (for repetition draw) and how often they've happened
-    pub significant_boards: HashMap<Board, u8>,
+    //Hashes of every position since the last capture or pawn move, including this one (for repetition draw)
+    pub history: Vec<u64>,
+    //Zobrist hash of the position, kept up to date as moves are made
+    pub hash: u64,
+}
+
+//What a null move changed, so it can be undone
+pub struct NullMoveUndo {
+    last_pawn: Option<Square>,
+    hash: u64,
+    history: Vec<u64>,
 }
 
 impl ChessBoard {
     pub fn new() -> Self {
-        return Self {
+        let mut board = Self {
             board: Board(STARTING_BOARD.clone()),
             move_number: 0,
             fifty_move_counter: 100,
             castle_status: [true; 4],
             last_pawn: None,
             game_status: GameStatus::Ongoing,
-            significant_boards: HashMap::new(),
+            history: Vec::new(),
+            hash: 0,
         };
+        board.hash = board.compute_hash();
+        board.history.push(board.hash);
+        return board;
     }
 
     pub fn move_piece(&mut self, piece: Pieces, team: Teams, location: Position, target: Position, promotion: Option<Pieces>) -> bool {
@@ -91,7 +131,7 @@ impl ChessBoard {
                     Some(promoting) => if promoting.promotable_into() {
                         self.board[promoting as usize + team as usize] += target;
                         self.board[piece as usize + team as usize] ^= location;
-                        self.last_pawn = Some(location);
+                        self.last_pawn = Square::try_from(location).ok();
                         self.check_game_status();
                         true
                     } else {
@@ -100,7 +140,7 @@ impl ChessBoard {
                     None => false
                 };
             } else {
-                self.last_pawn = Some(location);
+                self.last_pawn = Square::try_from(location).ok();
             }
         } else {
             self.last_pawn = None;
@@ -115,27 +155,133 @@ impl ChessBoard {
         return true;
     }
 
+    //Applies a move without checking that it's legal, for moves that came from the move generator.
+    //The hash is updated with only the keys that change.
+    pub fn make_move(&mut self, chess_move: &Move) {
+        let team = chess_move.team;
+        let mut hash = self.hash ^ self.en_passant_hash() ^ side_key();
+        let board = &mut self.board.0;
+        let from = chess_move.from.bitboard().0;
+        let to = chess_move.to.bitboard().0;
+        let mut significant = chess_move.piece == Pieces::Pawn;
+
+        //A pawn moving diagonally onto an empty square takes en passant
+        let mut captured = to;
+        if chess_move.piece == Pieces::Pawn && board[12] & to == 0 && from.trailing_zeros() % 8 != to.trailing_zeros() % 8 {
+            captured = match team {
+                Teams::White => to >> 8,
+                Teams::Black => to << 8
+            };
+        }
+        if board[12] & captured != 0 {
+            for piece in (!team).pieces() {
+                if board[piece] & captured != 0 {
+                    board[piece] ^= captured;
+                    hash ^= piece_key(piece, captured.trailing_zeros() as usize);
+                    break;
+                }
+            }
+            board[12] ^= captured;
+            significant = true;
+        }
+
+        //Move the piece, swapping it for the promotion if there is one
+        let moved = chess_move.piece as usize + team as usize;
+        let placed = chess_move.promotion.unwrap_or(chess_move.piece) as usize + team as usize;
+        board[moved] ^= from;
+        board[placed] |= to;
+        board[12] = (board[12] ^ from) | to;
+        hash ^= piece_key(moved, chess_move.from.index()) ^ piece_key(placed, chess_move.to.index());
+
+        //Castling moves the king two squares, and the rook jumps over it
+        if chess_move.piece == Pieces::King && from.trailing_zeros().abs_diff(to.trailing_zeros()) == 2 {
+            let rook = if to > from {
+                (to << 1) | (to >> 1)
+            } else {
+                (to >> 2) | (to << 1)
+            };
+            board[Pieces::Rook as usize + team as usize] ^= rook;
+            board[12] ^= rook;
+            for square in Bitboard(rook) {
+                hash ^= piece_key(Pieces::Rook as usize + team as usize, square.index());
+            }
+        }
+
+        //Moving the king or a rook, or losing a rook in its corner, gives up castling on that side
+        for (index, rook) in CASTLE_ROOKS.iter().enumerate() {
+            if self.castle_status[index] && (chess_move.from == *rook || chess_move.to == *rook ||
+                (chess_move.piece == Pieces::King && chess_move.from == KING_STARTS[index / 2])) {
+                self.castle_status[index] = false;
+                hash ^= castle_key(index);
+            }
+        }
+
+        self.last_pawn = if chess_move.piece == Pieces::Pawn && from.trailing_zeros().abs_diff(to.trailing_zeros()) == 16 {
+            Some(chess_move.to)
+        } else {
+            None
+        };
+        self.fifty_move_counter = if significant {
+            100
+        } else {
+            self.fifty_move_counter.saturating_sub(1)
+        };
+        self.move_number += 1;
+        self.hash = hash ^ self.en_passant_hash();
+        if significant {
+            self.history.clear();
+        }
+        self.history.push(self.hash);
+    }
+
+    //Passes the turn without moving, for null move pruning. Refused while in check, since the king can't be left there.
+    //Positions before it can't be repeated through it, so the history starts over until it's undone.
+    pub fn make_null_move(&mut self) -> Option<NullMoveUndo> {
+        if self.in_check() {
+            return None;
+        }
+        let undo = NullMoveUndo {
+            last_pawn: self.last_pawn,
+            hash: self.hash,
+            history: mem::take(&mut self.history),
+        };
+        self.hash ^= self.en_passant_hash() ^ side_key();
+        self.last_pawn = None;
+        self.move_number += 1;
+        self.history.push(self.hash);
+        return Some(undo);
+    }
+
+    pub fn unmake_null_move(&mut self, undo: NullMoveUndo) {
+        self.last_pawn = undo.last_pawn;
+        self.move_number -= 1;
+        self.hash = undo.hash;
+        self.history = undo.history;
+    }
+
+    //Whether the position has happened before since the last capture or pawn move
+    pub fn is_repetition(&self) -> bool {
+        return self.repetitions() > 1;
+    }
+
+    //How many times the position has happened since the last capture or pawn move, including now
+    pub fn repetitions(&self) -> usize {
+        return self.history.iter().filter(|hash| **hash == self.hash).count();
+    }
+
     fn check_game_status(&mut self) {
         self.move_number += 1;
+        self.hash = self.compute_hash();
         if self.fifty_move_counter == 0 {
-            self.game_status = GameStatus::DrawByFiftyMoveRule;
+            self.game_status = GameStatus::draw(Termination::FiftyMoveRule);
             return;
         }
         self.fifty_move_counter -= 1;
 
-        match self.significant_boards.get_mut(&self.board[0..12]) {
-            Some(count) => {
-                if count == &3 {
-                    self.game_status = GameStatus::DrawByRepetition;
-                    return;
-                }
-                *count -= 1;
-            }
-            None => {
-                let mut adding = [0u64; 12];
-                adding.clone_from_slice(&self.board[0..12]);
-                self.significant_boards.insert(adding, 1);
-            }
+        self.history.push(self.hash);
+        if self.repetitions() >= 3 {
+            self.game_status = GameStatus::draw(Termination::Repetition);
+            return;
         }
 
         self.game_status = self.checkmate_or_stalemate(Teams::White);
@@ -164,23 +310,29 @@ impl ChessBoard {
         }
 
         return if win_if_no_moves {
-            (!team).win_status()
+            (!team).win_status(Termination::Checkmate)
         } else {
-            GameStatus::DrawByStalemate
+            GameStatus::draw(Termination::Stalemate)
         }
     }
 
-    fn get_pieces(&self, piece: usize) -> Vec<Position> {
-        let mut output = Vec::new();
-
-        let mut index = 1u64;
-        for _ in 0..64 {
-            if (self.board[piece] & index) != 0 {
-                output.push(Position(index));
+    //The piece and team standing on the square, if any
+    pub fn piece_at(&self, square: Square) -> Option<(Pieces, Teams)> {
+        if !self.board.occupied().contains(square) {
+            return None;
+        }
+        for team in [Teams::White, Teams::Black] {
+            for piece in team.pieces() {
+                if Bitboard(self.board.0[piece]).contains(square) {
+                    return Some((Pieces::from(piece as u8), team));
+                }
             }
-            index <<= 1;
         }
-        return output;
+        return None;
+    }
+
+    fn get_pieces(&self, piece: usize) -> Vec<Position> {
+        return Bitboard(self.board.0[piece]).squares().map(Position::from).collect();
     }
 }
 
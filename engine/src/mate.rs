@@ -0,0 +1,131 @@
+use crate::ChessBoard;
+use crate::movelist::MoveList;
+use crate::util::Move;
+
+//Outcome of looking for a forced mate
+#[derive(Clone)]
+pub enum MateResult {
+    //The side to move mates in the given number of moves. The line alternates attacker and defender moves
+    //and ends with the mating move, with the defender putting up the longest resistance.
+    Mate(u8, Vec<Move>),
+    //No forced mate within the limit. With checks_only set, only lines where every attacking move checks were tried.
+    NoMate,
+    //The node limit ran out before the search could finish
+    Unknown,
+}
+
+//Alpha-beta style mate search with the side to move as the attacker. It only proves or refutes forced mates,
+//so nothing is evaluated, and it deepens one move at a time so the first mate found is the shortest.
+pub struct MateSearch {
+    //Only try checking moves for the attacker, which is much faster but misses mates that start quietly
+    pub checks_only: bool,
+    pub max_nodes: Option<u64>,
+    nodes: u64,
+    stopped: bool,
+}
+
+impl MateSearch {
+    pub fn new() -> Self {
+        return Self {
+            checks_only: false,
+            max_nodes: None,
+            nodes: 0,
+            stopped: false,
+        };
+    }
+
+    //Looks for a mate in at most the given number of the attacker's moves
+    pub fn solve(&mut self, board: &ChessBoard, moves: u8) -> MateResult {
+        self.nodes = 0;
+        self.stopped = false;
+        for depth in 1..=moves {
+            if let Some(mut line) = self.attack(board, depth) {
+                line.reverse();
+                return MateResult::Mate(depth, line);
+            }
+            if self.stopped {
+                return MateResult::Unknown;
+            }
+        }
+        return MateResult::NoMate;
+    }
+
+    //Nodes searched by the last call to solve
+    #[inline]
+    pub fn nodes(&self) -> u64 {
+        return self.nodes;
+    }
+
+    //A move that mates within the depth whatever the defender does, with the line built backwards
+    fn attack(&mut self, board: &ChessBoard, depth: u8) -> Option<Vec<Move>> {
+        //Checks go first, since they're the likeliest to mate and leave the defender the fewest replies.
+        //The last move has to check anyway.
+        let mut checks = MoveList::new();
+        board.generate_checks(&mut checks);
+        let mut moves = checks.clone();
+        if !self.checks_only && depth > 1 {
+            let mut all = MoveList::new();
+            board.generate_moves(&mut all);
+            for chess_move in &all {
+                if !checks.contains(chess_move) {
+                    moves.push(*chess_move);
+                }
+            }
+        }
+
+        for chess_move in &moves {
+            if !self.count_node() {
+                return None;
+            }
+            let mut next = board.clone();
+            next.make_move(chess_move);
+            if let Some(mut line) = self.defend(&next, depth) {
+                line.push(*chess_move);
+                return Some(line);
+            }
+        }
+        return None;
+    }
+
+    //The longest line to mate over every reply, or None if any reply escapes. depth counts the attacker's
+    //move that was just made. Each reply is deepened one move at a time like the root, so the attacker's answer
+    //is always the fastest mate and the lines being compared are the shortest ones.
+    fn defend(&mut self, board: &ChessBoard, depth: u8) -> Option<Vec<Move>> {
+        let mut replies = MoveList::new();
+        board.generate_moves(&mut replies);
+        if replies.is_empty() {
+            return if board.in_check() { Some(Vec::new()) } else { None };
+        }
+        if depth == 1 {
+            return None;
+        }
+
+        let mut longest: Option<Vec<Move>> = None;
+        for reply in &replies {
+            let mut next = board.clone();
+            next.make_move(reply);
+            let mut line = (1..depth).find_map(|remaining| self.attack(&next, remaining))?;
+            line.push(*reply);
+            if longest.as_ref().is_none_or(|longest| line.len() > longest.len()) {
+                longest = Some(line);
+            }
+        }
+        return longest;
+    }
+
+    //Whether the search can carry on
+    #[inline]
+    fn count_node(&mut self) -> bool {
+        self.nodes += 1;
+        if self.max_nodes.is_some_and(|max_nodes| self.nodes > max_nodes) {
+            self.stopped = true;
+        }
+        return !self.stopped;
+    }
+}
+
+impl Default for MateSearch {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
@@ -0,0 +1,309 @@
+use crate::ChessBoard;
+use crate::attacks::{between, bishop_attacks, line, pawn_attacks, piece_attacks, rook_attacks};
+use crate::bitboard::{Bitboard, Square};
+use crate::movelist::MoveList;
+use crate::pieces::{BOTTOM_ROW, Pieces, SECOND_ROW, SEVENTH_ROW, TOP_ROW};
+use crate::util::{CastleDirection, GameStatus, Move, Teams, Termination};
+
+pub const PROMOTIONS: [Pieces; 4] = [Pieces::Queen, Pieces::Rook, Pieces::Bishop, Pieces::Knight];
+
+//Starting king square for each team, white first
+pub const KING_STARTS: [Square; 2] = [Square(4), Square(60)];
+//Rook corners, indexed like castle_status
+pub const CASTLE_ROOKS: [Square; 4] = [Square(0), Square(7), Square(56), Square(63)];
+
+//Which moves a generator call produces. Promotions count as captures, since they change the material too.
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Stage {
+    All,
+    Captures,
+    Quiets,
+}
+
+//Index into castle_status for the team castling in the direction
+#[inline]
+pub fn castle_index(team: Teams, direction: CastleDirection) -> usize {
+    return team.index() * 2 + direction as usize;
+}
+
+impl ChessBoard {
+    #[inline]
+    pub fn in_check(&self) -> bool {
+        return !self.checkers().is_empty();
+    }
+
+    //Legal moves for the side to move
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut output = MoveList::new();
+        self.generate_moves(&mut output);
+        return output.to_vec();
+    }
+
+    //The generators below add legal moves for the side to move to the buffer, without clearing it first
+
+    pub fn generate_moves(&self, output: &mut MoveList) {
+        self.generate_legal(Stage::All, output);
+    }
+
+    //Captures, including en passant, and every promotion
+    pub fn generate_captures(&self, output: &mut MoveList) {
+        self.generate_legal(Stage::Captures, output);
+    }
+
+    //Moves that don't capture or promote, including castling
+    pub fn generate_quiets(&self, output: &mut MoveList) {
+        self.generate_legal(Stage::Quiets, output);
+    }
+
+    //Moves that give check, of any kind
+    pub fn generate_checks(&self, output: &mut MoveList) {
+        let mut moves = MoveList::new();
+        self.generate_pseudo_legal(self.side_to_move(), Stage::All, &mut moves);
+        for chess_move in &moves {
+            if self.gives_check(chess_move) && self.is_legal(chess_move) {
+                output.push(*chess_move);
+            }
+        }
+    }
+
+    //Whether the move checks the enemy king, either from where the piece lands or by uncovering a slider.
+    //Worked out from the attack tables without making the move.
+    pub fn gives_check(&self, chess_move: &Move) -> bool {
+        let team = chess_move.team;
+        let king = match self.king_square(!team) {
+            Some(king) => king,
+            None => return false
+        };
+        let board = &self.board;
+        let mut occupied = board.occupied().without(chess_move.from).with(chess_move.to);
+        let mut straight = (board.get_board(Pieces::Rook, team) | board.get_board(Pieces::Queen, team)).without(chess_move.from);
+        let diagonal = (board.get_board(Pieces::Bishop, team) | board.get_board(Pieces::Queen, team)).without(chess_move.from);
+
+        //En passant also empties the square of the pawn taken, which can open a line to the king
+        if chess_move.piece == Pieces::Pawn && chess_move.from.file() != chess_move.to.file() && !board.occupied().contains(chess_move.to) {
+            occupied = occupied.without(Square::new(chess_move.to.file(), chess_move.from.rank()));
+        }
+        //Castling can check with the rook once it's jumped over the king
+        if chess_move.piece == Pieces::King && chess_move.from.0.abs_diff(chess_move.to.0) == 2 {
+            let (rook_from, rook_to) = if chess_move.to.0 > chess_move.from.0 {
+                (Square(chess_move.to.0 + 1), Square(chess_move.to.0 - 1))
+            } else {
+                (Square(chess_move.to.0 - 2), Square(chess_move.to.0 + 1))
+            };
+            occupied = occupied.without(rook_from).with(rook_to);
+            straight = straight.without(rook_from).with(rook_to);
+        }
+
+        let placed = chess_move.promotion.unwrap_or(chess_move.piece);
+        return piece_attacks(placed, team, chess_move.to, occupied).contains(king) ||
+            rook_attacks(king, occupied).intersects(straight) || bishop_attacks(king, occupied).intersects(diagonal);
+    }
+
+    fn generate_legal(&self, stage: Stage, output: &mut MoveList) {
+        let mut moves = MoveList::new();
+        self.generate_pseudo_legal(self.side_to_move(), stage, &mut moves);
+        for chess_move in &moves {
+            if self.is_legal(chess_move) {
+                output.push(*chess_move);
+            }
+        }
+    }
+
+    //Checkmate, stalemate or a dead position with too little material, which end the game without anyone asking.
+    //Repetition and the move counting rules depend on the game's history, so they're left to Game.
+    pub fn status(&self) -> GameStatus {
+        if self.legal_moves().is_empty() {
+            return if self.in_check() {
+                GameStatus::win(!self.side_to_move(), Termination::Checkmate)
+            } else {
+                GameStatus::draw(Termination::Stalemate)
+            };
+        }
+        if self.insufficient_material() {
+            return GameStatus::draw(Termination::InsufficientMaterial);
+        }
+        return GameStatus::Ongoing;
+    }
+
+    //Neither side can mate: bare kings, a single minor piece, or only bishops all on one colour of square
+    pub fn insufficient_material(&self) -> bool {
+        let board = &self.board.0;
+        let mut heavy = 0;
+        for team in [Teams::White, Teams::Black] {
+            for piece in [Pieces::Pawn, Pieces::Rook, Pieces::Queen] {
+                heavy |= board[piece as usize + team as usize];
+            }
+        }
+        if heavy != 0 {
+            return false;
+        }
+
+        let knights = board[Pieces::Knight as usize] | board[Pieces::Knight as usize + Teams::Black as usize];
+        let bishops = board[Pieces::Bishop as usize] | board[Pieces::Bishop as usize + Teams::Black as usize];
+        let minors = (knights | bishops).count_ones();
+        if minors <= 1 {
+            return true;
+        }
+        const LIGHT_SQUARES: u64 = 0x55AA_55AA_55AA_55AA;
+        return knights == 0 && (bishops & LIGHT_SQUARES == 0 || bishops & !LIGHT_SQUARES == 0);
+    }
+
+    //Whether making the move leaves the mover's king safe. The move must come from pseudo_legal_moves.
+    //Worked out from checks and pins without making the move.
+    pub fn is_legal(&self, chess_move: &Move) -> bool {
+        let team = chess_move.team;
+        let king = match self.king_square(team) {
+            Some(king) => king,
+            None => return false
+        };
+        let enemy = self.team_pieces(!team);
+        let occupied = self.board.occupied();
+
+        if chess_move.piece == Pieces::King {
+            //Castling is only generated when the king doesn't start, pass or land in check
+            if chess_move.from.0.abs_diff(chess_move.to.0) == 2 {
+                return true;
+            }
+            //Without the king in the way, a slider checking it also covers the square behind
+            return !self.attackers_with_occupancy(chess_move.to, occupied.without(chess_move.from)).intersects(enemy);
+        }
+
+        //En passant empties two squares on the same rank, so the lines to the king are checked directly
+        if chess_move.piece == Pieces::Pawn && chess_move.from.file() != chess_move.to.file() && !occupied.contains(chess_move.to) {
+            let taken = Square::new(chess_move.to.file(), chess_move.from.rank());
+            let after = occupied.without(chess_move.from).without(taken).with(chess_move.to);
+            return !self.attackers_with_occupancy(king, after).intersects(enemy);
+        }
+
+        //Any other move has to take or block a single checker, and a pinned piece has to stay on its pin
+        let checkers = self.attackers_of(king, !team);
+        if let Some(checker) = checkers.first() {
+            if !checkers.is_single() || !between(king, checker).with(checker).contains(chess_move.to) {
+                return false;
+            }
+        }
+        return !self.pinned_pieces(team).contains(chess_move.from) || line(king, chess_move.from).contains(chess_move.to);
+    }
+
+    //Moves that follow how the pieces move, but may leave the king in check
+    pub fn pseudo_legal_moves(&self, team: Teams) -> Vec<Move> {
+        let mut output = MoveList::new();
+        self.generate_pseudo_legal(team, Stage::All, &mut output);
+        return output.to_vec();
+    }
+
+    fn generate_pseudo_legal(&self, team: Teams, stage: Stage, output: &mut MoveList) {
+        let own = self.team_pieces(team);
+        let enemy = self.team_pieces(!team);
+        let occupied = self.board.occupied();
+
+        //Pawns push into empty squares and take diagonally, including en passant
+        let (start_row, last_row, forward) = match team {
+            Teams::White => (SECOND_ROW, TOP_ROW, 1),
+            Teams::Black => (SEVENTH_ROW, BOTTOM_ROW, -1)
+        };
+        let en_passant = self.en_passant_target(team);
+        let (pawn_mask, piece_mask) = match stage {
+            Stage::All => (Bitboard::FULL, Bitboard::FULL),
+            Stage::Captures => (enemy | en_passant | last_row, enemy),
+            Stage::Quiets => (!(enemy | en_passant | last_row), !enemy)
+        };
+        for from in self.board.get_board(Pieces::Pawn, team) {
+            let mut targets = pawn_attacks(team, from) & (enemy | en_passant);
+            if let Some(single) = from.offset(0, forward).filter(|single| !occupied.contains(*single)) {
+                targets = targets.with(single);
+                if let Some(double) = single.offset(0, forward).filter(|double| start_row.contains(from) && !occupied.contains(*double)) {
+                    targets = targets.with(double);
+                }
+            }
+            Self::push_pawn_moves(output, team, from, targets & pawn_mask, last_row);
+        }
+
+        for piece in [Pieces::Knight, Pieces::Bishop, Pieces::Rook, Pieces::Queen, Pieces::King] {
+            for from in self.board.get_board(piece, team) {
+                let targets = piece_attacks(piece, team, from, occupied) & !own & piece_mask;
+                Self::push_moves(output, piece, team, from, targets);
+            }
+        }
+
+        if stage != Stage::Captures {
+            self.push_castles(output, team);
+        }
+    }
+
+    //The square a pawn of the team could take en passant on, as a mask
+    pub fn en_passant_target(&self, team: Teams) -> Bitboard {
+        let pawn = match self.last_pawn {
+            Some(pawn) if self.board.get_board(Pieces::Pawn, !team).contains(pawn) => pawn,
+            _ => return Bitboard::EMPTY
+        };
+        let target = pawn.offset(0, match team {
+            Teams::White => 1,
+            Teams::Black => -1
+        });
+        return match target {
+            Some(target) if !self.board.occupied().contains(target) => target.bitboard(),
+            _ => Bitboard::EMPTY
+        };
+    }
+
+    fn push_castles(&self, output: &mut MoveList, team: Teams) {
+        let king = KING_STARTS[team.index()];
+        if !self.board.get_board(Pieces::King, team).contains(king) {
+            return;
+        }
+
+        for direction in [CastleDirection::Left, CastleDirection::Right] {
+            let index = castle_index(team, direction);
+            let rook = CASTLE_ROOKS[index];
+            if !self.castle_status[index] || !self.board.get_board(Pieces::Rook, team).contains(rook) {
+                continue;
+            }
+
+            //Everything between the king and rook must be empty, and the king can't pass through check
+            let step = if rook > king { 1 } else { -1 };
+            let passed = Square((king.0 as i8 + step) as u8);
+            let target = Square((king.0 as i8 + 2 * step) as u8);
+            if between(king, rook).intersects(self.board.occupied()) ||
+                [king, passed, target].iter().any(|square| self.is_attacked(*square, !team)) {
+                continue;
+            }
+
+            output.push(Move {
+                piece: Pieces::King,
+                team,
+                from: king,
+                to: target,
+                promotion: None,
+            });
+        }
+    }
+
+    fn push_pawn_moves(output: &mut MoveList, team: Teams, from: Square, targets: Bitboard, last_row: Bitboard) {
+        Self::push_moves(output, Pieces::Pawn, team, from, targets & !last_row);
+
+        for to in targets & last_row {
+            for promotion in PROMOTIONS {
+                output.push(Move {
+                    piece: Pieces::Pawn,
+                    team,
+                    from,
+                    to,
+                    promotion: Some(promotion),
+                });
+            }
+        }
+    }
+
+    fn push_moves(output: &mut MoveList, piece: Pieces, team: Teams, from: Square, targets: Bitboard) {
+        for to in targets {
+            output.push(Move {
+                piece,
+                team,
+                from,
+                to,
+                promotion: None,
+            });
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::ops::{Deref, DerefMut};
+use crate::bitboard::Square;
+use crate::pieces::Pieces;
+use crate::util::{Move, Teams};
+
+//More than the most legal moves any position has (218)
+pub const MAX_MOVES: usize = 256;
+
+//Fills the unused part of the buffer
+const EMPTY_MOVE: Move = Move {
+    piece: Pieces::Pawn,
+    team: Teams::White,
+    from: Square(0),
+    to: Square(0),
+    promotion: None,
+};
+
+//A fixed-capacity move buffer that lives on the stack, so generating moves doesn't allocate.
+//It derefs to a slice of the moves pushed so far.
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    pub fn new() -> Self {
+        return Self {
+            moves: [EMPTY_MOVE; MAX_MOVES],
+            len: 0,
+        };
+    }
+
+    //Panics when full, which no legal position can cause
+    #[inline]
+    pub fn push(&mut self, chess_move: Move) {
+        self.moves[self.len] = chess_move;
+        self.len += 1;
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    #[inline]
+    fn deref(&self) -> &[Move] {
+        return &self.moves[..self.len];
+    }
+}
+
+impl DerefMut for MoveList {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [Move] {
+        return &mut self.moves[..self.len];
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        return self.iter();
+    }
+}
@@ -1,5 +1,7 @@
 use crate::{ChessBoard, Pieces, Teams};
-use crate::util::Position;
+use crate::bitboard::{File, Rank, Square};
+use crate::ordering::captured_piece;
+use crate::util::{Move, Position};
 
 pub fn parse_notation(moves: &str) -> ChessBoard {
     let mut board = ChessBoard::new();
@@ -51,4 +53,122 @@ pub fn parse_notation(moves: &str) -> ChessBoard {
         }
     }
     return board;
-}
\ No newline at end of file
+}
+
+impl ChessBoard {
+    //Writes a legal move in standard algebraic notation, like Nbd7, exd5, e8=Q+ or O-O-O
+    pub fn to_san(&self, chess_move: &Move) -> String {
+        let from = chess_move.from;
+        let to = chess_move.to;
+        let mut output = String::new();
+
+        if chess_move.piece == Pieces::King && from.0.abs_diff(to.0) == 2 {
+            output.push_str(if to > from { "O-O" } else { "O-O-O" });
+        } else {
+            let capture = captured_piece(self, chess_move).is_some();
+            if chess_move.piece == Pieces::Pawn {
+                if capture {
+                    output.push_str(&from.file().to_string());
+                }
+            } else {
+                output.push(chess_move.piece.letter(Teams::White));
+
+                //Name the file, rank or both when another piece of the same kind can also get there
+                let others: Vec<Square> = self.legal_moves().iter()
+                    .filter(|other| other.piece == chess_move.piece && other.to == to && other.from != from)
+                    .map(|other| other.from)
+                    .collect();
+                if !others.is_empty() {
+                    if others.iter().all(|other| other.file() != from.file()) {
+                        output.push_str(&from.file().to_string());
+                    } else if others.iter().all(|other| other.rank() != from.rank()) {
+                        output.push_str(&from.rank().to_string());
+                    } else {
+                        output.push_str(&from.to_string());
+                    }
+                }
+            }
+            if capture {
+                output.push('x');
+            }
+            output.push_str(&to.to_string());
+            if let Some(promotion) = chess_move.promotion {
+                output.push('=');
+                output.push(promotion.letter(Teams::White));
+            }
+        }
+
+        let mut next = self.clone();
+        next.make_move(chess_move);
+        if next.in_check() {
+            output.push(if next.legal_moves().is_empty() { '#' } else { '+' });
+        }
+        return output;
+    }
+
+    //Reads a move in standard algebraic notation, or in coordinate notation like e7e8q.
+    //Returns None unless exactly one legal move matches.
+    pub fn parse_san(&self, text: &str) -> Option<Move> {
+        let text = text.trim_end_matches(|character| "+#!?".contains(character));
+        let moves = self.legal_moves();
+
+        if let Some(found) = moves.iter().find(|chess_move| chess_move.to_string() == text) {
+            return Some(*found);
+        }
+
+        let castle = match text {
+            "O-O" | "0-0" => Some(true),
+            "O-O-O" | "0-0-0" => Some(false),
+            _ => None
+        };
+        if let Some(kingside) = castle {
+            return moves.into_iter().find(|chess_move| {
+                chess_move.piece == Pieces::King && chess_move.from.0.abs_diff(chess_move.to.0) == 2 &&
+                    (chess_move.to > chess_move.from) == kingside
+            });
+        }
+
+        //Piece letter, then any of the from file and rank, an optional x, the target square and a promotion
+        let mut characters: Vec<char> = text.chars().filter(|character| *character != 'x' && *character != '=').collect();
+        let piece = match characters.first() {
+            Some(letter) if letter.is_ascii_uppercase() => {
+                let (piece, _) = Pieces::from_letter(*letter)?;
+                characters.remove(0);
+                piece
+            }
+            _ => Pieces::Pawn
+        };
+        let promotion = match characters.last() {
+            Some(letter) if letter.is_ascii_uppercase() => {
+                let (promotion, _) = Pieces::from_letter(*letter)?;
+                characters.pop();
+                Some(promotion)
+            }
+            _ => None
+        };
+        if characters.len() < 2 {
+            return None;
+        }
+        let target: String = characters[characters.len() - 2..].iter().collect();
+        let target = Square::parse(&target)?;
+        let mut from_file = None;
+        let mut from_rank = None;
+        for character in &characters[..characters.len() - 2] {
+            match character {
+                'a'..='h' => from_file = Some(File(*character as u8 - b'a')),
+                '1'..='8' => from_rank = Some(Rank(*character as u8 - b'1')),
+                _ => return None
+            }
+        }
+
+        let mut matching = moves.into_iter().filter(|chess_move| {
+            chess_move.piece == piece && chess_move.to == target && chess_move.promotion == promotion &&
+                from_file.map_or(true, |file| chess_move.from.file() == file) && from_rank.map_or(true, |rank| chess_move.from.rank() == rank)
+        });
+        let found = matching.next()?;
+        if matching.next().is_some() {
+            return None;
+        }
+        return Some(found);
+    }
+}
@@ -0,0 +1,202 @@
+use crate::ChessBoard;
+use crate::pieces::Pieces;
+use crate::search::MAX_PLY;
+use crate::util::Move;
+
+//Score bands, so each kind of move sorts ahead of the next
+const TABLE_MOVE: i32 = 1_000_000;
+const CAPTURE: i32 = 100_000;
+const FIRST_KILLER: i32 = 90_000;
+const SECOND_KILLER: i32 = 80_000;
+//History scores are halved whenever one gets this big, keeping them under the killer band
+const HISTORY_LIMIT: i32 = 50_000;
+
+//Victim values for most valuable victim, least valuable attacker, indexed by Pieces
+const VICTIM_VALUES: [i32; 6] = [100, 300, 300, 500, 900, 0];
+
+//Which heuristic the move that caused a beta cutoff came from
+#[derive(Clone, Copy)]
+pub enum MoveSource {
+    TableMove,
+    Capture,
+    Killer,
+    Quiet,
+}
+
+//Counters for judging how well moves are ordered
+#[derive(Clone, Copy, Default)]
+pub struct OrderingStats {
+    //Beta cutoffs in total, and how many came from the first move searched
+    pub cutoffs: u64,
+    pub first_move_cutoffs: u64,
+    pub table_move_cutoffs: u64,
+    pub capture_cutoffs: u64,
+    pub killer_cutoffs: u64,
+    pub quiet_cutoffs: u64,
+    //Sum of how far into the move list each cutoff happened, for the average
+    pub cutoff_index_total: u64,
+}
+
+//Move ordering with a table move, MVV-LVA for captures, two killer moves per ply and a history heuristic
+pub struct MoveOrdering {
+    killers: [[Option<Move>; 2]; MAX_PLY + 1],
+    //Indexed by team, from square and to square
+    history: Box<[[[i32; 64]; 64]; 2]>,
+    pub stats: OrderingStats,
+}
+
+impl OrderingStats {
+    //Fraction of cutoffs that came from the first move, the usual measure of ordering quality
+    pub fn first_move_rate(&self) -> f64 {
+        if self.cutoffs == 0 {
+            return 0.0;
+        }
+        return self.first_move_cutoffs as f64 / self.cutoffs as f64;
+    }
+
+    //Average position in the move list of the move that caused a cutoff, starting at 0
+    pub fn average_cutoff_index(&self) -> f64 {
+        if self.cutoffs == 0 {
+            return 0.0;
+        }
+        return self.cutoff_index_total as f64 / self.cutoffs as f64;
+    }
+}
+
+impl MoveOrdering {
+    pub fn new() -> Self {
+        return Self {
+            killers: [[None; 2]; MAX_PLY + 1],
+            history: Box::new([[[0; 64]; 64]; 2]),
+            stats: OrderingStats::default(),
+        };
+    }
+
+    //Forgets killers and history, for a new game
+    pub fn clear(&mut self) {
+        self.killers = [[None; 2]; MAX_PLY + 1];
+        self.history = Box::new([[[0; 64]; 64]; 2]);
+        self.stats = OrderingStats::default();
+    }
+
+    //Keeps history from the last search but lets newer results outweigh it
+    pub fn new_search(&mut self) {
+        self.killers = [[None; 2]; MAX_PLY + 1];
+        for team in self.history.iter_mut() {
+            for from in team.iter_mut() {
+                for score in from.iter_mut() {
+                    *score /= 2;
+                }
+            }
+        }
+        self.stats = OrderingStats::default();
+    }
+
+    //Sorts the moves so the most promising come first
+    pub fn order(&self, board: &ChessBoard, moves: &mut [Move], table_move: Option<Move>, ply: usize) {
+        moves.sort_by_cached_key(|chess_move| -self.score(board, chess_move, table_move, ply));
+    }
+
+    pub fn score(&self, board: &ChessBoard, chess_move: &Move, table_move: Option<Move>, ply: usize) -> i32 {
+        if table_move == Some(*chess_move) {
+            return TABLE_MOVE;
+        }
+        if let Some(victim) = captured_piece(board, chess_move) {
+            return CAPTURE + mvv_lva(victim, chess_move.piece);
+        }
+        if let Some(promotion) = chess_move.promotion {
+            return CAPTURE + VICTIM_VALUES[promotion as usize] - VICTIM_VALUES[Pieces::Pawn as usize];
+        }
+        if let Some(killers) = self.killers.get(ply) {
+            if killers[0] == Some(*chess_move) {
+                return FIRST_KILLER;
+            }
+            if killers[1] == Some(*chess_move) {
+                return SECOND_KILLER;
+            }
+        }
+        return self.history(chess_move);
+    }
+
+    #[inline]
+    pub fn history(&self, chess_move: &Move) -> i32 {
+        return self.history[chess_move.team.index()][chess_move.from.index()][chess_move.to.index()];
+    }
+
+    //Records the move that caused a beta cutoff, at the given index of the ordered move list
+    pub fn record_cutoff(&mut self, board: &ChessBoard, chess_move: &Move, table_move: Option<Move>, depth: u8, ply: usize, index: usize) {
+        self.stats.cutoffs += 1;
+        self.stats.cutoff_index_total += index as u64;
+        if index == 0 {
+            self.stats.first_move_cutoffs += 1;
+        }
+
+        let source = self.source(board, chess_move, table_move, ply);
+        match source {
+            MoveSource::TableMove => self.stats.table_move_cutoffs += 1,
+            MoveSource::Capture => self.stats.capture_cutoffs += 1,
+            MoveSource::Killer => self.stats.killer_cutoffs += 1,
+            MoveSource::Quiet => self.stats.quiet_cutoffs += 1
+        }
+
+        //Captures are already ordered well by MVV-LVA, so only quiet moves train the killers and history
+        if captured_piece(board, chess_move).is_some() || chess_move.promotion.is_some() {
+            return;
+        }
+
+        if let Some(killers) = self.killers.get_mut(ply) {
+            if killers[0] != Some(*chess_move) {
+                killers[1] = killers[0];
+                killers[0] = Some(*chess_move);
+            }
+        }
+
+        let score = &mut self.history[chess_move.team.index()][chess_move.from.index()][chess_move.to.index()];
+        *score += depth as i32 * depth as i32;
+        if *score >= HISTORY_LIMIT {
+            for team in self.history.iter_mut() {
+                for from in team.iter_mut() {
+                    for score in from.iter_mut() {
+                        *score /= 2;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn source(&self, board: &ChessBoard, chess_move: &Move, table_move: Option<Move>, ply: usize) -> MoveSource {
+        if table_move == Some(*chess_move) {
+            return MoveSource::TableMove;
+        }
+        if captured_piece(board, chess_move).is_some() || chess_move.promotion.is_some() {
+            return MoveSource::Capture;
+        }
+        if let Some(killers) = self.killers.get(ply) {
+            if killers.contains(&Some(*chess_move)) {
+                return MoveSource::Killer;
+            }
+        }
+        return MoveSource::Quiet;
+    }
+}
+
+//Most valuable victim first, then least valuable attacker
+#[inline]
+pub fn mvv_lva(victim: Pieces, attacker: Pieces) -> i32 {
+    return VICTIM_VALUES[victim as usize] * 10 - attacker as i32;
+}
+
+//The piece the move takes, including pawns taken en passant
+pub fn captured_piece(board: &ChessBoard, chess_move: &Move) -> Option<Pieces> {
+    if let Some((piece, team)) = board.piece_at(chess_move.to) {
+        return if team != chess_move.team {
+            Some(piece)
+        } else {
+            None
+        };
+    }
+    if chess_move.piece == Pieces::Pawn && chess_move.from.file() != chess_move.to.file() {
+        return Some(Pieces::Pawn);
+    }
+    return None;
+}
@@ -0,0 +1,213 @@
+use crate::ChessBoard;
+use crate::bitboard::Bitboard;
+use crate::pieces::Pieces;
+use crate::util::{Board, Directions, Teams};
+use crate::zobrist::piece_key;
+
+//Pawn features for both teams, as bitboards of the pawns (or files) that have them. Arrays are indexed by
+//team.index(), white first.
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
+pub struct PawnStructure {
+    //No enemy pawn can block or take them on the way to promoting
+    passed: [Bitboard; 2],
+    //No friendly pawns on either neighbouring file
+    isolated: [Bitboard; 2],
+    //Pawns with another friendly pawn in front of them on the same file, so one per extra pawn
+    doubled: [Bitboard; 2],
+    //Can't safely advance, and no friendly pawn can come up to defend the square in front
+    backward: [Bitboard; 2],
+    //Defended by a friendly pawn or standing next to one
+    connected: [Bitboard; 2],
+    //Not passed yet, but on a file free of enemy pawns with at least as many helpers as enemy pawns guarding the way
+    candidate: [Bitboard; 2],
+    //Whole files with no pawns on them
+    open_files: Bitboard,
+    //Whole files with enemy pawns but none of the team's own
+    half_open_files: [Bitboard; 2],
+}
+
+//Pawn structures cached by the pawn-only hash, since pawns move far less often than the rest of the pieces
+pub struct PawnTable {
+    entries: Vec<Option<(u64, PawnStructure)>>,
+}
+
+impl PawnStructure {
+    pub fn new(board: &Board) -> Self {
+        let mut output = Self::default();
+        let pawns = [board.get_board(Pieces::Pawn, Teams::White), board.get_board(Pieces::Pawn, Teams::Black)];
+        output.open_files = !file_fill(pawns[0] | pawns[1]);
+
+        for team in [Teams::White, Teams::Black] {
+            let own = pawns[team.index()];
+            let enemy = pawns[(!team).index()];
+            let own_attacks = pawn_set_attacks(team, own);
+            let enemy_attacks = pawn_set_attacks(!team, enemy);
+            //Squares an enemy pawn could still reach or take on before promoting, on its own and neighbouring files
+            let enemy_span = front_span(!team, enemy);
+            let enemy_guard = enemy_span | enemy_span.shift(Directions::East) | enemy_span.shift(Directions::West);
+
+            let passed = own & !enemy_guard;
+            let own_files = file_fill(own);
+            output.passed[team.index()] = passed;
+            output.isolated[team.index()] = own & !(own_files.shift(Directions::East) | own_files.shift(Directions::West));
+            output.doubled[team.index()] = own & rear_span(team, own);
+            output.connected[team.index()] = own & (own_attacks | own.shift(Directions::East) | own.shift(Directions::West));
+            output.half_open_files[team.index()] = !own_files & file_fill(enemy);
+
+            //The stop square is covered by an enemy pawn and no friendly pawn behind or beside can cover it
+            let stops = forward(team, own);
+            let support = fill(team, own_attacks);
+            output.backward[team.index()] = own & forward(!team, stops & enemy_attacks & !support);
+
+            let free_file = own & !enemy_span & !passed;
+            for square in free_file {
+                let neighbours = square.file().bitboard().shift(Directions::East) | square.file().bitboard().shift(Directions::West);
+                let ahead = front_span(team, square.bitboard());
+                let ahead = ahead.shift(Directions::East) | ahead.shift(Directions::West);
+                let helpers = own & neighbours & !ahead;
+                let sentries = enemy & ahead;
+                if helpers.count() >= sentries.count() {
+                    output.candidate[team.index()] = output.candidate[team.index()].with(square);
+                }
+            }
+        }
+        return output;
+    }
+
+    #[inline]
+    pub fn passed(&self, team: Teams) -> Bitboard {
+        return self.passed[team.index()];
+    }
+
+    #[inline]
+    pub fn isolated(&self, team: Teams) -> Bitboard {
+        return self.isolated[team.index()];
+    }
+
+    #[inline]
+    pub fn doubled(&self, team: Teams) -> Bitboard {
+        return self.doubled[team.index()];
+    }
+
+    #[inline]
+    pub fn backward(&self, team: Teams) -> Bitboard {
+        return self.backward[team.index()];
+    }
+
+    #[inline]
+    pub fn connected(&self, team: Teams) -> Bitboard {
+        return self.connected[team.index()];
+    }
+
+    #[inline]
+    pub fn candidate(&self, team: Teams) -> Bitboard {
+        return self.candidate[team.index()];
+    }
+
+    #[inline]
+    pub fn open_files(&self) -> Bitboard {
+        return self.open_files;
+    }
+
+    #[inline]
+    pub fn half_open_files(&self, team: Teams) -> Bitboard {
+        return self.half_open_files[team.index()];
+    }
+}
+
+impl PawnTable {
+    //Entries are about 120 bytes each
+    pub fn new(entries: usize) -> Self {
+        return Self {
+            entries: vec![None; entries.max(1)],
+        };
+    }
+
+    //The pawn structure of the position, computed only when it isn't already cached
+    pub fn probe(&mut self, board: &ChessBoard) -> PawnStructure {
+        let key = board.pawn_hash();
+        let index = (key % self.entries.len() as u64) as usize;
+        if let Some((stored, structure)) = self.entries[index] {
+            if stored == key {
+                return structure;
+            }
+        }
+        let structure = PawnStructure::new(&board.board);
+        self.entries[index] = Some((key, structure));
+        return structure;
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+}
+
+impl ChessBoard {
+    //Hashes only the pawns, with the same keys as compute_hash
+    pub fn pawn_hash(&self) -> u64 {
+        let mut hash = 0;
+        for team in [Teams::White, Teams::Black] {
+            for square in self.board.get_board(Pieces::Pawn, team) {
+                hash ^= piece_key(Pieces::Pawn as usize + team as usize, square.index());
+            }
+        }
+        return hash;
+    }
+
+    //The pawn structure computed from scratch. Use a PawnTable when asking for many positions.
+    pub fn pawn_structure(&self) -> PawnStructure {
+        return PawnStructure::new(&self.board);
+    }
+}
+
+//Every square one step forward for the team
+#[inline]
+fn forward(team: Teams, squares: Bitboard) -> Bitboard {
+    return squares.shift(match team {
+        Teams::White => Directions::North,
+        Teams::Black => Directions::South
+    });
+}
+
+//The squares plus everything in front of them for the team
+fn fill(team: Teams, squares: Bitboard) -> Bitboard {
+    let mut bits = squares.0;
+    match team {
+        Teams::White => {
+            bits |= bits << 8;
+            bits |= bits << 16;
+            bits |= bits << 32;
+        }
+        Teams::Black => {
+            bits |= bits >> 8;
+            bits |= bits >> 16;
+            bits |= bits >> 32;
+        }
+    }
+    return Bitboard(bits);
+}
+
+//Everything strictly in front of the squares
+#[inline]
+fn front_span(team: Teams, squares: Bitboard) -> Bitboard {
+    return fill(team, forward(team, squares));
+}
+
+//Everything strictly behind the squares
+#[inline]
+fn rear_span(team: Teams, squares: Bitboard) -> Bitboard {
+    return front_span(!team, squares);
+}
+
+//The whole files that have any of the squares
+#[inline]
+fn file_fill(squares: Bitboard) -> Bitboard {
+    return fill(Teams::White, squares) | fill(Teams::Black, squares);
+}
+
+//Every square attacked by the team's pawns
+#[inline]
+fn pawn_set_attacks(team: Teams, pawns: Bitboard) -> Bitboard {
+    let ahead = forward(team, pawns);
+    return ahead.shift(Directions::East) | ahead.shift(Directions::West);
+}
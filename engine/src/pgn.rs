@@ -0,0 +1,168 @@
+use std::fs;
+use std::mem;
+use crate::game::Game;
+
+//A game read from PGN, with its tags in the order they were written
+#[derive(Clone)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub game: Game,
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        return self.tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str());
+    }
+}
+
+//Reads every game in a PGN collection, failing on the first game that can't be read
+pub fn parse_pgn(text: &str) -> Result<Vec<PgnGame>, String> {
+    return read_games(text).into_iter().collect();
+}
+
+//Reads every game in a PGN collection. A game that can't be read is skipped up to its result and given as an
+//error naming its place in the collection, so one bad game doesn't lose the rest of an archive. Only the main
+//line is kept: variations and NAGs are skipped, and comments are attached to the move before them.
+pub fn read_games(text: &str) -> Vec<Result<PgnGame, String>> {
+    let mut output = Vec::new();
+    let mut tags = Vec::new();
+    let mut game: Option<Game> = None;
+    let mut error: Option<String> = None;
+    //Whether the movetext of the current game has started, so the next tag begins another game
+    let mut started = false;
+    let mut characters = text.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '[' => {
+                //A tag after moves means the last game had no result written
+                if started {
+                    finish(&mut output, &mut tags, &mut game, &mut error);
+                    started = false;
+                }
+                let mut tag = String::new();
+                let mut quoted = false;
+                let mut escaped = false;
+                for character in characters.by_ref() {
+                    match character {
+                        ']' if !quoted => break,
+                        '"' if !escaped => quoted = !quoted,
+                        _ => {}
+                    }
+                    escaped = character == '\\' && !escaped;
+                    tag.push(character);
+                }
+                match parse_tag(&tag) {
+                    Some(tag) => tags.push(tag),
+                    None => {
+                        error.get_or_insert_with(|| format!("Invalid tag [{}]", tag));
+                    }
+                }
+            }
+            '{' => {
+                let comment: String = characters.by_ref().take_while(|character| *character != '}').collect();
+                started = true;
+                if error.is_none() {
+                    match start_game(&mut game, &tags) {
+                        Ok(game) => if !game.is_empty() {
+                            game.set_comment(game.len() - 1, Some(comment.split_whitespace().collect::<Vec<&str>>().join(" ")));
+                        },
+                        Err(message) => error = Some(message)
+                    }
+                }
+            }
+            ';' => {
+                characters.by_ref().take_while(|character| *character != '\n').for_each(drop);
+            }
+            '(' => {
+                let mut depth = 1;
+                while depth > 0 {
+                    match characters.next() {
+                        Some('(') => depth += 1,
+                        Some(')') => depth -= 1,
+                        Some('{') => characters.by_ref().take_while(|character| *character != '}').for_each(drop),
+                        Some(_) => {}
+                        None => {
+                            error.get_or_insert_with(|| String::from("Unclosed variation"));
+                            break;
+                        }
+                    }
+                }
+            }
+            _ if character.is_whitespace() => {}
+            _ => {
+                let mut token = String::from(character);
+                while let Some(next) = characters.peek() {
+                    if next.is_whitespace() || "[]{}();".contains(*next) {
+                        break;
+                    }
+                    token.push(*next);
+                    characters.next();
+                }
+                started = true;
+                match token.as_str() {
+                    "1-0" | "0-1" | "1/2-1/2" | "*" => {
+                        if error.is_none() {
+                            if let Err(message) = start_game(&mut game, &tags) {
+                                error = Some(message);
+                            }
+                        }
+                        finish(&mut output, &mut tags, &mut game, &mut error);
+                        started = false;
+                    }
+                    _ if token.starts_with('$') || error.is_some() => {}
+                    _ => {
+                        //Move numbers like 12. or 12... can be written right against the move. The digits are only
+                        //stripped when a dot follows them, since 0-0 is castling.
+                        let rest = token.trim_start_matches(|character: char| character.is_ascii_digit());
+                        let san = if rest.is_empty() || rest.starts_with('.') { rest.trim_start_matches('.') } else { token.as_str() };
+                        if !san.is_empty() {
+                            if let Err(message) = start_game(&mut game, &tags).and_then(|game| game.play_san(san)) {
+                                error = Some(message);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    if started || error.is_some() {
+        finish(&mut output, &mut tags, &mut game, &mut error);
+    }
+    return output;
+}
+
+pub fn load_pgn(path: &str) -> Result<Vec<PgnGame>, String> {
+    return match fs::read_to_string(path) {
+        Ok(text) => parse_pgn(&text),
+        Err(error) => Err(format!("Failed to read {}: {}", path, error))
+    };
+}
+
+//Ends the current game, which is an error if anything in it couldn't be read
+fn finish(output: &mut Vec<Result<PgnGame, String>>, tags: &mut Vec<(String, String)>, game: &mut Option<Game>, error: &mut Option<String>) {
+    let tags = mem::take(tags);
+    let game = game.take();
+    if let Some(error) = error.take() {
+        output.push(Err(format!("Game {}: {}", output.len() + 1, error)));
+    } else if let Some(game) = game {
+        output.push(Ok(PgnGame { tags, game }));
+    }
+}
+
+//The game being read, starting it from the FEN tag if there is one
+fn start_game<'a>(game: &'a mut Option<Game>, tags: &[(String, String)]) -> Result<&'a mut Game, String> {
+    if game.is_none() {
+        *game = Some(match tags.iter().find(|(name, _)| name == "FEN") {
+            Some((_, fen)) => Game::from_fen(fen)?,
+            None => Game::new()
+        });
+    }
+    return Ok(game.as_mut().unwrap());
+}
+
+//Splits Event "Battle \"3\"" into the name and unescaped value
+fn parse_tag(tag: &str) -> Option<(String, String)> {
+    let (name, value) = tag.trim().split_once(char::is_whitespace)?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    return Some((String::from(name), value.replace("\\\"", "\"").replace("\\\\", "\\")));
+}
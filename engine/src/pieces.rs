@@ -1,16 +1,17 @@
 use std::fmt::{Display, Formatter};
 use crate::ChessBoard;
-use crate::util::{PiecePositions, Position, Row, Teams};
+use crate::bitboard::Bitboard;
+use crate::util::{Position, Teams};
 
 pub const FIRST_SQUARE: u64 = 0b1;
-pub const TOP_ROW: Row = Row(0xFF_00_00_00_00_00_00_00);
-pub const SECOND_ROW: Row = Row(0x00_FF_00_00_00_00_00_00);
-pub const SEVENTH_ROW: Row = Row(0x00_00_00_00_00_00_FF_00);
-pub const BOTTOM_ROW: Row = Row(0x00_00_00_00_00_00_00_FF);
-pub const LEFT_SIDE: Row = Row(0x90_90_90_90_90_90_90_90);
-pub const RIGHT_SIDE: Row = Row(0x01_01_01_01_01_01_01_01);
+pub const TOP_ROW: Bitboard = Bitboard(0xFF_00_00_00_00_00_00_00);
+pub const SECOND_ROW: Bitboard = Bitboard(0x00_00_00_00_00_00_FF_00);
+pub const SEVENTH_ROW: Bitboard = Bitboard(0x00_FF_00_00_00_00_00_00);
+pub const BOTTOM_ROW: Bitboard = Bitboard(0x00_00_00_00_00_00_00_FF);
+pub const LEFT_SIDE: Bitboard = Bitboard(0x90_90_90_90_90_90_90_90);
+pub const RIGHT_SIDE: Bitboard = Bitboard(0x01_01_01_01_01_01_01_01);
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub enum Pieces {
     Pawn = 0,
     Knight = 1,
@@ -255,13 +256,38 @@ impl From<u8> for Pieces {
             1 => Pieces::Knight,
             2 => Pieces::Bishop,
             3 => Pieces::Rook,
-            4 => Pieces::King,
-            5 => Pieces::Queen,
+            4 => Pieces::Queen,
+            5 => Pieces::King,
             _ => panic!("Wtf?!?")
         };
     }
 }
 
+impl Pieces {
+    //The letter used in FEN and algebraic notation, uppercase for white
+    pub fn letter(&self, team: Teams) -> char {
+        let letter = ['P', 'N', 'B', 'R', 'Q', 'K'][*self as usize];
+        return match team {
+            Teams::White => letter,
+            Teams::Black => letter.to_ascii_lowercase()
+        };
+    }
+
+    pub fn from_letter(letter: char) -> Option<(Pieces, Teams)> {
+        let piece = match letter.to_ascii_uppercase() {
+            'P' => Pieces::Pawn,
+            'N' => Pieces::Knight,
+            'B' => Pieces::Bishop,
+            'R' => Pieces::Rook,
+            'Q' => Pieces::Queen,
+            'K' => Pieces::King,
+            _ => return None
+        };
+        let team = if letter.is_ascii_uppercase() { Teams::White } else { Teams::Black };
+        return Some((piece, team));
+    }
+}
+
 impl Display for Pieces {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         return match self {
@@ -282,7 +308,7 @@ impl From<char> for Pieces {
             'N' => Pieces::Knight,
             'B' => Pieces::Bishop,
             'R' => Pieces::Rook,
-            'K' => Pieces::Rook,
+            'K' => Pieces::King,
             _ => Pieces::Pawn
         };
     }
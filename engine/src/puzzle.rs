@@ -0,0 +1,270 @@
+use crate::ChessBoard;
+use crate::pgn::PgnGame;
+use crate::search::{Search, SearchLimits, SearchResult};
+use crate::util::{Move, Teams};
+
+//What makes a position from a game a puzzle
+#[derive(Clone)]
+pub struct PuzzleSettings {
+    //For every search: each position of the game, and each legal move tried when checking a move of the solution is the only good one
+    pub limits: SearchLimits,
+    //How far in centipawns the best move has to be ahead of every other move
+    pub margin: i32,
+    //Only positions where this player was to move, matched against the White and Black tags
+    pub player: Option<String>,
+    //Plies at the start of each game to skip, which are usually still book moves
+    pub min_ply: usize,
+    //The longest solution kept, in plies. It always ends with a move by the side solving the puzzle,
+    //and stops before any of that side's moves that isn't ahead of the others by the margin.
+    pub max_solution: usize,
+}
+
+#[derive(Clone)]
+pub struct Puzzle {
+    pub fen: String,
+    //The winning move and the line the search expects to follow it, where every move of the solving side is the only good one
+    pub solution: Vec<Move>,
+    //What was played in the game, and whether it was the solution
+    pub played: Move,
+    pub found: bool,
+    //For the side solving the puzzle once the winning move is played, and how far that is ahead of the next best move
+    pub score: i32,
+    pub margin: i32,
+    //Plies into the game
+    pub ply: usize,
+    pub event: Option<String>,
+    pub white: Option<String>,
+    pub black: Option<String>,
+}
+
+impl PuzzleSettings {
+    pub fn new(limits: SearchLimits) -> Self {
+        return Self {
+            limits,
+            margin: 200,
+            player: None,
+            min_ply: 8,
+            max_solution: 7,
+        };
+    }
+}
+
+//Every puzzle found in the games
+pub fn find_puzzles(games: &[PgnGame], search: &mut Search, settings: &PuzzleSettings) -> Vec<Puzzle> {
+    let mut output = Vec::new();
+    for game in games {
+        output.extend(game_puzzles(game, search, settings));
+    }
+    return output;
+}
+
+//Positions where one move wins clearly more than any other. Only positions next to a big swing in the game are
+//checked in full: the move played there either threw the advantage away, or answered a blunder that handed it over.
+pub fn game_puzzles(game: &PgnGame, search: &mut Search, settings: &PuzzleSettings) -> Vec<Puzzle> {
+    let moves = game.game.moves();
+    let scores: Vec<i32> = (0..=moves.len())
+        .map(|index| analyse(search, game.game.position(index).unwrap(), settings.limits).score)
+        .collect();
+
+    let mut output = Vec::new();
+    for (index, played) in moves.iter().enumerate().skip(settings.min_ply) {
+        let board = game.game.position(index).unwrap();
+        if !plays_for(game, board.side_to_move(), settings) {
+            continue;
+        }
+        //How much the move before and this move lost for the side that made it
+        let answered = index > 0 && scores[index - 1] + scores[index] >= settings.margin;
+        let missed = scores[index] + scores[index + 1] >= settings.margin;
+        if !answered && !missed {
+            continue;
+        }
+        if let Some(puzzle) = verify(board, &played.chess_move, search, settings) {
+            output.push(Puzzle {
+                ply: index,
+                event: game.tag("Event").map(String::from),
+                white: game.tag("White").map(String::from),
+                black: game.tag("Black").map(String::from),
+                ..puzzle
+            });
+        }
+    }
+    return output;
+}
+
+//The best move of a position, found by searching every legal move
+struct Ranking {
+    best: Move,
+    score: i32,
+    //What the search expects to follow the best move
+    line: Vec<Move>,
+    //How far the best move is ahead of the next best, or None if it's the only legal move
+    lead: Option<i32>,
+}
+
+//Keeps the position if the best move is ahead of the rest by the margin, then follows the expected line for as long
+//as each move of the solving side is just as clear
+fn verify(board: &ChessBoard, played: &Move, search: &mut Search, settings: &PuzzleSettings) -> Option<Puzzle> {
+    let first = rank_moves(board, search, settings)?;
+    let lead = first.lead?;
+    if lead < settings.margin {
+        return None;
+    }
+
+    let mut solution = vec![first.best];
+    let mut position = board.clone();
+    position.make_move(&first.best);
+    let mut line = first.line;
+    while solution.len() + 2 <= settings.max_solution {
+        let reply = match line.first() {
+            Some(reply) => *reply,
+            None => break
+        };
+        let mut next = position.clone();
+        next.make_move(&reply);
+        let ranking = match rank_moves(&next, search, settings) {
+            Some(ranking) => ranking,
+            None => break
+        };
+        if ranking.lead.is_some_and(|lead| lead < settings.margin) {
+            break;
+        }
+        solution.push(reply);
+        solution.push(ranking.best);
+        next.make_move(&ranking.best);
+        position = next;
+        line = ranking.line;
+    }
+
+    return Some(Puzzle {
+        fen: board.to_fen(),
+        solution,
+        played: *played,
+        found: *played == first.best,
+        score: first.score,
+        margin: lead,
+        ply: 0,
+        event: None,
+        white: None,
+        black: None,
+    });
+}
+
+//Searches every legal move, or returns None if there aren't any
+fn rank_moves(board: &ChessBoard, search: &mut Search, settings: &PuzzleSettings) -> Option<Ranking> {
+    let mut best: Option<Ranking> = None;
+    let mut second: Option<i32> = None;
+    for chess_move in board.legal_moves() {
+        let mut next = board.clone();
+        next.make_move(&chess_move);
+        let result = analyse(search, &next, settings.limits);
+        let score = -result.score;
+        match &best {
+            Some(ranking) if score <= ranking.score => second = Some(second.map_or(score, |second| second.max(score))),
+            _ => {
+                if let Some(ranking) = &best {
+                    second = Some(second.map_or(ranking.score, |second| second.max(ranking.score)));
+                }
+                best = Some(Ranking {
+                    best: chess_move,
+                    score,
+                    line: result.principal_variation,
+                    lead: None,
+                });
+            }
+        }
+    }
+
+    let mut ranking = best?;
+    ranking.lead = second.map(|second| ranking.score - second);
+    return Some(ranking);
+}
+
+fn plays_for(game: &PgnGame, team: Teams, settings: &PuzzleSettings) -> bool {
+    return match &settings.player {
+        Some(player) => game.tag(match team {
+            Teams::White => "White",
+            Teams::Black => "Black"
+        }) == Some(player.as_str()),
+        None => true
+    };
+}
+
+//Each position is searched fresh so scores don't depend on what was searched before
+fn analyse(search: &mut Search, board: &ChessBoard, limits: SearchLimits) -> SearchResult {
+    search.limits = limits;
+    search.table.clear();
+    search.ordering.clear();
+    return search.run(board);
+}
+
+//One puzzle per line after a header, with moves in coordinate notation separated by spaces
+pub fn to_csv(puzzles: &[Puzzle]) -> String {
+    let mut output = String::from("fen,solution,played,found,score,margin,ply,event,white,black\n");
+    for puzzle in puzzles {
+        let fields = [
+            puzzle.fen.clone(),
+            move_list(&puzzle.solution),
+            puzzle.played.to_string(),
+            puzzle.found.to_string(),
+            puzzle.score.to_string(),
+            puzzle.margin.to_string(),
+            puzzle.ply.to_string(),
+            puzzle.event.clone().unwrap_or_default(),
+            puzzle.white.clone().unwrap_or_default(),
+            puzzle.black.clone().unwrap_or_default(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| {
+            if field.contains([',', '"', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        }).collect();
+        output.push_str(&fields.join(","));
+        output.push('\n');
+    }
+    return output;
+}
+
+//A JSON array of puzzle objects, with the solution as an array of moves in coordinate notation
+pub fn to_json(puzzles: &[Puzzle]) -> String {
+    let mut output = String::from("[");
+    for (index, puzzle) in puzzles.iter().enumerate() {
+        if index > 0 {
+            output.push(',');
+        }
+        let solution: Vec<String> = puzzle.solution.iter().map(|chess_move| json_string(&chess_move.to_string())).collect();
+        output.push_str(&format!("\n  {{\"fen\": {}, \"solution\": [{}], \"played\": {}, \"found\": {}, \"score\": {}, \"margin\": {}, \"ply\": {}, \
+            \"event\": {}, \"white\": {}, \"black\": {}}}",
+            json_string(&puzzle.fen), solution.join(", "), json_string(&puzzle.played.to_string()), puzzle.found, puzzle.score,
+            puzzle.margin, puzzle.ply, json_option(&puzzle.event), json_option(&puzzle.white), json_option(&puzzle.black)));
+    }
+    output.push_str(if puzzles.is_empty() { "]\n" } else { "\n]\n" });
+    return output;
+}
+
+fn move_list(moves: &[Move]) -> String {
+    return moves.iter().map(|chess_move| chess_move.to_string()).collect::<Vec<String>>().join(" ");
+}
+
+fn json_option(text: &Option<String>) -> String {
+    return match text {
+        Some(text) => json_string(text),
+        None => String::from("null")
+    };
+}
+
+fn json_string(text: &str) -> String {
+    let mut output = String::from("\"");
+    for character in text.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            _ if character.is_control() => output.push_str(&format!("\\u{:04x}", character as u32)),
+            _ => output.push(character)
+        }
+    }
+    output.push('"');
+    return output;
+}
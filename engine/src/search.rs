@@ -0,0 +1,466 @@
+use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::ChessBoard;
+use crate::compact::CompactMove;
+use crate::eval::EvalWeights;
+use crate::movelist::MoveList;
+use crate::ordering::{MoveOrdering, OrderingStats, captured_piece};
+use crate::see::DEFAULT_PIECE_VALUES;
+use crate::time::{Clock, TimeManager};
+use crate::transposition::{Bound, TranspositionTable};
+use crate::pieces::Pieces;
+use crate::util::Move;
+
+pub const INFINITY: i32 = 32000;
+pub const MATE_SCORE: i32 = 30000;
+pub const MAX_PLY: usize = 128;
+//Scores past this are forced mates, with the distance to mate taken off MATE_SCORE
+pub const MATE_BOUND: i32 = MATE_SCORE - MAX_PLY as i32;
+
+//How many nodes to search between checks of the clock
+const CHECK_INTERVAL: u64 = 1024;
+pub const DEFAULT_TABLE_SIZE: usize = 16;
+//Room left for positional gains when delta pruning captures in quiescence
+const DELTA_MARGIN: i32 = 200;
+
+//When to stop searching. Any limit that is set can end the search, and no limits searches until MAX_PLY.
+#[derive(Clone, Copy)]
+pub struct SearchLimits {
+    pub depth: Option<u8>,
+    //Counted over all threads
+    pub nodes: Option<u64>,
+    pub time: Option<Duration>,
+    //Lets the time manager pick soft and hard deadlines from the clock
+    pub clock: Option<Clock>,
+}
+
+#[derive(Clone)]
+pub struct SearchResult {
+    //None when the side to move has no legal moves
+    pub best_move: Option<Move>,
+    //Score for the side to move, in centipawns or MATE_SCORE minus the plies to mate
+    pub score: i32,
+    pub principal_variation: Vec<Move>,
+    //The deepest iteration that finished
+    pub depth: u8,
+    //Nodes in total, and how many of those were in quiescence
+    pub nodes: u64,
+    pub quiescence_nodes: u64,
+    //Nodes searched by each thread, with the main thread first
+    pub thread_nodes: Vec<u64>,
+    pub ordering: OrderingStats,
+    pub elapsed: Duration,
+}
+
+//Iterative deepening negamax with alpha-beta pruning.
+//With more than one thread it runs Lazy SMP: every thread searches the same position and they share the table.
+pub struct Search {
+    pub limits: SearchLimits,
+    pub weights: EvalWeights,
+    //Kept between runs, so searching the next move of a game reuses earlier work
+    pub table: TranspositionTable,
+    //The main thread's move ordering. Helper threads start fresh each run.
+    pub ordering: MoveOrdering,
+    pub threads: usize,
+}
+
+//Everything one thread needs to search
+struct Worker<'a> {
+    //0 for the main thread, which decides when to stop and whose result is used
+    id: usize,
+    limits: SearchLimits,
+    weights: &'a EvalWeights,
+    table: &'a TranspositionTable,
+    ordering: MoveOrdering,
+    stop: &'a AtomicBool,
+    //Nodes over all threads, added in batches of CHECK_INTERVAL and at the end of every iteration
+    total_nodes: &'a AtomicU64,
+    nodes: u64,
+    //How many of this thread's nodes have been added to total_nodes
+    flushed_nodes: u64,
+    //With no other threads the node limit can be checked exactly
+    single_thread: bool,
+    quiescence_nodes: u64,
+    start: Instant,
+    time: Option<TimeManager>,
+    stopped: bool,
+    completed_depth: u8,
+    //Principal variation of the last finished iteration, searched first in the next one
+    previous_pv: Vec<Move>,
+}
+
+impl SearchLimits {
+    pub fn depth(depth: u8) -> Self {
+        return Self { depth: Some(depth), nodes: None, time: None, clock: None };
+    }
+
+    pub fn nodes(nodes: u64) -> Self {
+        return Self { depth: None, nodes: Some(nodes), time: None, clock: None };
+    }
+
+    pub fn time(time: Duration) -> Self {
+        return Self { depth: None, nodes: None, time: Some(time), clock: None };
+    }
+
+    pub fn clock(clock: Clock) -> Self {
+        return Self { depth: None, nodes: None, time: None, clock: Some(clock) };
+    }
+}
+
+impl Search {
+    pub fn new(limits: SearchLimits) -> Self {
+        return Self::with_weights(limits, EvalWeights::new());
+    }
+
+    pub fn with_weights(limits: SearchLimits, weights: EvalWeights) -> Self {
+        return Self {
+            limits,
+            weights,
+            table: TranspositionTable::new(DEFAULT_TABLE_SIZE),
+            ordering: MoveOrdering::new(),
+            threads: 1,
+        };
+    }
+
+    pub fn run(&mut self, board: &ChessBoard) -> SearchResult {
+        let start = Instant::now();
+        let stop = AtomicBool::new(false);
+        let total_nodes = AtomicU64::new(0);
+        self.table.new_search();
+        self.ordering.new_search();
+
+        let ordering = mem::replace(&mut self.ordering, MoveOrdering::new());
+        let search = &*self;
+        let mut main = Worker::new(0, search, ordering, &stop, &total_nodes, start);
+        let (mut result, helper_nodes) = thread::scope(|scope| {
+            let mut helpers = Vec::new();
+            for id in 1..search.threads.max(1) {
+                let mut helper = Worker::new(id, search, MoveOrdering::new(), &stop, &total_nodes, start);
+                helpers.push(scope.spawn(move || {
+                    helper.iterate(board);
+                    return (helper.nodes, helper.quiescence_nodes);
+                }));
+            }
+
+            let result = main.iterate(board);
+            stop.store(true, Ordering::Relaxed);
+
+            let helper_nodes: Vec<(u64, u64)> = helpers.into_iter().map(|helper| helper.join().unwrap()).collect();
+            return (result, helper_nodes);
+        });
+
+        result.thread_nodes = vec![main.nodes];
+        for (nodes, quiescence_nodes) in helper_nodes {
+            result.thread_nodes.push(nodes);
+            result.nodes += nodes;
+            result.quiescence_nodes += quiescence_nodes;
+        }
+        result.elapsed = start.elapsed();
+        self.ordering = main.ordering;
+        return result;
+    }
+}
+
+impl<'a> Worker<'a> {
+    fn new(id: usize, search: &'a Search, ordering: MoveOrdering, stop: &'a AtomicBool, total_nodes: &'a AtomicU64, start: Instant) -> Self {
+        return Self {
+            id,
+            limits: search.limits,
+            weights: &search.weights,
+            table: &search.table,
+            ordering,
+            stop,
+            total_nodes,
+            nodes: 0,
+            flushed_nodes: 0,
+            single_thread: search.threads <= 1,
+            quiescence_nodes: 0,
+            start,
+            time: search.limits.clock.map(|clock| TimeManager::new(&clock)),
+            stopped: false,
+            completed_depth: 0,
+            previous_pv: Vec::new(),
+        };
+    }
+
+    fn iterate(&mut self, board: &ChessBoard) -> SearchResult {
+        let mut result = SearchResult {
+            best_move: None,
+            score: self.terminal_score(board, 0),
+            principal_variation: Vec::new(),
+            depth: 0,
+            nodes: 0,
+            quiescence_nodes: 0,
+            thread_nodes: Vec::new(),
+            ordering: OrderingStats::default(),
+            elapsed: Duration::ZERO,
+        };
+        let legal_moves = board.legal_moves().len();
+        if legal_moves == 0 {
+            return result;
+        }
+        if let Some(time) = &mut self.time {
+            if legal_moves == 1 {
+                time.single_legal_move();
+            }
+        }
+
+        let max_depth = self.limits.depth.unwrap_or(MAX_PLY as u8).min(MAX_PLY as u8);
+        //Half the helpers start a depth ahead, so the threads don't all search the same tree in step
+        let first_depth = if self.id % 2 == 1 { 2.min(max_depth) } else { 1 };
+        for depth in first_depth..=max_depth {
+            let mut pv = Vec::new();
+            let score = self.negamax(board, depth, 0, -INFINITY, INFINITY, &mut pv);
+            self.flush_nodes();
+            //An unfinished iteration can't be trusted, so keep the last finished one
+            if self.stopped {
+                break;
+            }
+
+            self.completed_depth = depth;
+            result.best_move = pv.first().copied();
+            result.score = score;
+            result.depth = depth;
+            result.principal_variation = pv.clone();
+            self.previous_pv = pv;
+
+            //No point searching deeper once a forced mate is found
+            if score.abs() >= MATE_BOUND || self.limit_reached() {
+                break;
+            }
+            if let Some(time) = &mut self.time {
+                time.update(result.best_move, score);
+                if !time.should_start_iteration(self.start.elapsed()) {
+                    break;
+                }
+            }
+        }
+
+        result.nodes = self.nodes;
+        result.quiescence_nodes = self.quiescence_nodes;
+        result.ordering = self.ordering.stats;
+        return result;
+    }
+
+    fn negamax(&mut self, board: &ChessBoard, depth: u8, ply: usize, mut alpha: i32, beta: i32, pv: &mut Vec<Move>) -> i32 {
+        pv.clear();
+        if ply > 0 && (board.fifty_move_counter == 0 || board.is_repetition()) {
+            return 0;
+        }
+        if depth == 0 || ply >= MAX_PLY {
+            return self.quiescence(board, ply, alpha, beta);
+        }
+
+        self.count_node(false);
+        if self.stopped {
+            return 0;
+        }
+
+        //A deep enough earlier search of this position can answer for this one, except at the root which needs a move
+        let entry = self.table.probe(board.hash, ply);
+        if let Some(entry) = entry {
+            if ply > 0 && entry.depth >= depth {
+                match entry.bound {
+                    Bound::Exact => return entry.score,
+                    Bound::Lower if entry.score >= beta => return entry.score,
+                    Bound::Upper if entry.score <= alpha => return entry.score,
+                    _ => {}
+                }
+            }
+        }
+
+        let mut moves = board.legal_moves();
+        if moves.is_empty() {
+            return self.terminal_score(board, ply);
+        }
+
+        //The table's best move goes first, or failing that the last iteration's principal variation
+        let table_move = entry.and_then(|entry| entry.best_move.decode(board)).or(self.previous_pv.get(ply).copied());
+        self.ordering.order(board, &mut moves, table_move, ply);
+
+        let original_alpha = alpha;
+        let mut best = -INFINITY;
+        let mut best_move = None;
+        let mut child_pv = Vec::new();
+        for (index, chess_move) in moves.into_iter().enumerate() {
+            let mut child = board.clone();
+            child.make_move(&chess_move);
+            let score = -self.negamax(&child, depth - 1, ply + 1, -beta, -alpha, &mut child_pv);
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best {
+                best = score;
+                best_move = Some(chess_move);
+                if score > alpha {
+                    alpha = score;
+                    pv.clear();
+                    pv.push(chess_move);
+                    pv.extend_from_slice(&child_pv);
+                }
+                if alpha >= beta {
+                    self.ordering.record_cutoff(board, &chess_move, table_move, depth, ply, index);
+                    break;
+                }
+            }
+        }
+
+        let bound = if best >= beta {
+            Bound::Lower
+        } else if best > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        let best_move = best_move.map_or(CompactMove::NULL, |best_move| CompactMove::new(board, &best_move));
+        self.table.store(board.hash, depth, bound, best, best_move, ply);
+        return best;
+    }
+
+    //Searches captures and promotions until the position is quiet, so the evaluation isn't taken mid-exchange
+    fn quiescence(&mut self, board: &ChessBoard, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.count_node(true);
+        if self.stopped {
+            return 0;
+        }
+
+        //Standing pat isn't an option in check, so every evasion is searched instead
+        let in_check = board.in_check();
+        let stand_pat = self.weights.evaluate(board).relative(board.side_to_move());
+        if ply >= MAX_PLY {
+            return stand_pat;
+        }
+
+        let mut best = -INFINITY;
+        if !in_check {
+            if stand_pat >= beta {
+                return stand_pat;
+            }
+            //Even winning a queen wouldn't be enough
+            if stand_pat + DEFAULT_PIECE_VALUES[Pieces::Queen as usize] + DELTA_MARGIN < alpha {
+                return stand_pat;
+            }
+            best = stand_pat;
+            alpha = alpha.max(stand_pat);
+        }
+
+        let mut moves = MoveList::new();
+        if in_check {
+            board.generate_moves(&mut moves);
+        } else {
+            board.generate_captures(&mut moves);
+        }
+        if in_check && moves.is_empty() {
+            return self.terminal_score(board, ply);
+        }
+        self.ordering.order(board, &mut moves, None, ply);
+
+        for &chess_move in &moves {
+            //Delta pruning: skip captures that can't raise alpha even with a margin for positional gains
+            if !in_check && chess_move.promotion.is_none() {
+                if let Some(victim) = captured_piece(board, &chess_move) {
+                    if stand_pat + DEFAULT_PIECE_VALUES[victim as usize] + DELTA_MARGIN < alpha {
+                        continue;
+                    }
+                }
+            }
+
+            let mut child = board.clone();
+            child.make_move(&chess_move);
+            let score = -self.quiescence(&child, ply + 1, -beta, -alpha);
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best {
+                best = score;
+                if score > alpha {
+                    alpha = score;
+                }
+                if alpha >= beta {
+                    break;
+                }
+            }
+        }
+        return best;
+    }
+
+    //Counts a node and checks whether the search should stop. Nodes visited after stopping aren't searched, so they aren't counted.
+    #[inline]
+    fn count_node(&mut self, quiescence: bool) {
+        if self.stopped {
+            return;
+        }
+        self.nodes += 1;
+        if quiescence {
+            self.quiescence_nodes += 1;
+        }
+        //Only the main thread watches the limits, the helpers stop when it does
+        if self.nodes - self.flushed_nodes >= CHECK_INTERVAL {
+            self.flush_nodes();
+            if self.id == 0 && self.limit_reached() {
+                self.stop.store(true, Ordering::Relaxed);
+            }
+        } else if self.single_thread && self.node_limit_reached() {
+            self.stop.store(true, Ordering::Relaxed);
+        }
+        if self.stop.load(Ordering::Relaxed) {
+            self.stopped = true;
+        }
+    }
+
+    //Adds the nodes this thread hasn't reported yet to the total
+    fn flush_nodes(&mut self) {
+        self.total_nodes.fetch_add(self.nodes - self.flushed_nodes, Ordering::Relaxed);
+        self.flushed_nodes = self.nodes;
+    }
+
+    //Score for a position with no legal moves: mated if in check, otherwise stalemate
+    fn terminal_score(&self, board: &ChessBoard, ply: usize) -> i32 {
+        return if board.in_check() {
+            -MATE_SCORE + ply as i32
+        } else {
+            0
+        };
+    }
+
+    //Whether a node or time limit has run out. The first iteration always finishes so there's a move to play.
+    fn limit_reached(&self) -> bool {
+        if self.completed_depth == 0 {
+            return false;
+        }
+        if self.node_limit_reached() {
+            return true;
+        }
+        if let Some(time) = self.limits.time {
+            if self.start.elapsed() >= time {
+                return true;
+            }
+        }
+        if let Some(time) = &self.time {
+            if time.out_of_time(self.start.elapsed()) {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    //Whether the node limit has run out, counting this thread's nodes that haven't been added to the total yet
+    fn node_limit_reached(&self) -> bool {
+        if self.completed_depth == 0 {
+            return false;
+        }
+        return match self.limits.nodes {
+            Some(nodes) => self.total_nodes.load(Ordering::Relaxed) + self.nodes - self.flushed_nodes >= nodes,
+            None => false
+        };
+    }
+}
+
+//Searches the position with the default weights and returns the move to play
+pub fn best_move(board: &ChessBoard, limits: SearchLimits) -> Option<Move> {
+    return Search::new(limits).run(board).best_move;
+}
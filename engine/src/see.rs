@@ -0,0 +1,106 @@
+use crate::ChessBoard;
+use crate::attacks::{bishop_attacks, rook_attacks};
+use crate::bitboard::Square;
+use crate::pieces::Pieces;
+use crate::util::{Move, Teams};
+
+//Value of each piece for exchange evaluation, indexed by Pieces
+pub type PieceValues = [i32; 6];
+
+pub const DEFAULT_PIECE_VALUES: PieceValues = [100, 320, 330, 500, 900, 20000];
+
+//Pieces from cheapest to most expensive, for picking the next recapture
+const CAPTURE_ORDER: [Pieces; 6] = [Pieces::Pawn, Pieces::Knight, Pieces::Bishop, Pieces::Rook, Pieces::Queen, Pieces::King];
+
+impl ChessBoard {
+    //Material won (or lost if negative) by the side making the move once all captures on its square resolve
+    pub fn see(&self, chess_move: &Move) -> i32 {
+        return self.see_with_values(chess_move, &DEFAULT_PIECE_VALUES);
+    }
+
+    //Whether the move wins at least the threshold in material
+    pub fn see_ge(&self, chess_move: &Move, threshold: i32) -> bool {
+        return self.see_with_values(chess_move, &DEFAULT_PIECE_VALUES) >= threshold;
+    }
+
+    pub fn see_ge_with_values(&self, chess_move: &Move, threshold: i32, values: &PieceValues) -> bool {
+        return self.see_with_values(chess_move, values) >= threshold;
+    }
+
+    pub fn see_with_values(&self, chess_move: &Move, values: &PieceValues) -> i32 {
+        let board = &self.board;
+        let to = chess_move.to;
+        let mut occupied = board.occupied().without(chess_move.from);
+
+        //The piece standing on the target, or the pawn taken en passant next to it
+        let mut gain = [0i32; 32];
+        match self.piece_at(to) {
+            Some((piece, _)) => gain[0] = values[piece as usize],
+            None => if chess_move.piece == Pieces::Pawn && chess_move.from.file() != to.file() {
+                gain[0] = values[Pieces::Pawn as usize];
+                occupied = occupied.without(Square(match chess_move.team {
+                    Teams::White => to.0 - 8,
+                    Teams::Black => to.0 + 8
+                }));
+            }
+        }
+
+        //The piece left standing on the target after the move
+        let mut on_target = chess_move.piece;
+        if let Some(promotion) = chess_move.promotion {
+            gain[0] += values[promotion as usize] - values[Pieces::Pawn as usize];
+            on_target = promotion;
+        }
+
+        let diagonal = board.get_board(Pieces::Bishop, Teams::White) | board.get_board(Pieces::Queen, Teams::White) |
+            board.get_board(Pieces::Bishop, Teams::Black) | board.get_board(Pieces::Queen, Teams::Black);
+        let straight = board.get_board(Pieces::Rook, Teams::White) | board.get_board(Pieces::Queen, Teams::White) |
+            board.get_board(Pieces::Rook, Teams::Black) | board.get_board(Pieces::Queen, Teams::Black);
+
+        let mut attackers = self.attackers_with_occupancy(to, occupied);
+        let mut team = !chess_move.team;
+        let mut depth = 0;
+        loop {
+            let team_attackers = attackers & self.team_pieces(team);
+            if team_attackers.is_empty() {
+                break;
+            }
+
+            //Recapture with the least valuable piece
+            let mut next = None;
+            for piece in CAPTURE_ORDER {
+                if let Some(square) = (team_attackers & board.get_board(piece, team)).first() {
+                    next = Some((piece, square));
+                    break;
+                }
+            }
+            let (piece, square) = next.unwrap();
+
+            //The king can't capture into a defended square
+            if piece == Pieces::King && attackers.intersects(self.team_pieces(!team)) {
+                break;
+            }
+
+            //The recapture is known to be possible here, so whether it's worth making is left to the end
+            depth += 1;
+            gain[depth] = values[on_target as usize] - gain[depth - 1];
+
+            //Remove the capturer, revealing any slider behind it
+            occupied = occupied.without(square);
+            attackers = (attackers | (bishop_attacks(to, occupied) & diagonal) | (rook_attacks(to, occupied) & straight)) & occupied;
+            on_target = piece;
+            team = !team;
+
+            if depth == gain.len() - 1 {
+                break;
+            }
+        }
+
+        //Each side can stop capturing whenever it's ahead
+        while depth > 0 {
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+            depth -= 1;
+        }
+        return gain[0];
+    }
+}
@@ -0,0 +1,1162 @@
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Neg;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use crate::ChessBoard;
+use crate::attacks::king_attacks;
+use crate::bitboard::{Bitboard, Square};
+use crate::ordering::captured_piece;
+use crate::pieces::Pieces;
+use crate::util::{Move, Teams};
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+const MAX_PIECES: usize = 7;
+
+//Flags stored for each part of a table
+const STM: u8 = 1;
+const MAPPED: u8 = 2;
+const WIN_PLIES: u8 = 4;
+const LOSS_PLIES: u8 = 8;
+const WIDE: u8 = 16;
+const SINGLE_VALUE: u8 = 128;
+
+//Rank given to root moves that win or lose regardless of the fifty move rule
+const MAX_DTZ: i32 = 1 << 18;
+
+static INDICES: OnceLock<Indices> = OnceLock::new();
+
+//Win, draw or loss for the side to move. Cursed wins and blessed losses are drawn by the fifty move rule.
+#[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+//A legal move at the root with its tablebase result
+#[derive(Clone, Copy)]
+pub struct RootMove {
+    pub chess_move: Move,
+    //The result after the move, taking the fifty move counter into account
+    pub wdl: Wdl,
+    //Plies until the next capture or pawn move with best play, negative when losing
+    pub dtz: i32,
+    rank: i32,
+}
+
+//Syzygy tables found in a directory. Tables are read into memory the first time they are needed.
+pub struct Tablebase {
+    tables: HashMap<String, TableFiles>,
+    max_pieces: usize,
+}
+
+struct TableFiles {
+    material: Material,
+    wdl: LazyTable,
+    dtz: LazyTable,
+}
+
+struct LazyTable {
+    path: PathBuf,
+    table: OnceLock<Option<Table>>,
+}
+
+//What a table's name says about it, with the stronger side as white
+#[derive(Clone, Copy)]
+struct Material {
+    piece_count: usize,
+    has_pawns: bool,
+    //Whether any side has exactly one of a piece other than the king, which changes how pieces are indexed
+    has_unique_pieces: bool,
+    //Pawns of the side that leads the pawn indexing, then of the other side
+    pawn_count: [usize; 2],
+    //Both sides have the same pieces, so only white to move is stored
+    symmetric: bool,
+}
+
+struct Table {
+    data: Vec<u8>,
+    //Indexed by side to move (a single side for DTZ and symmetric tables), then the leading pawn's file
+    pairs: Vec<Vec<Pairs>>,
+    //Start of the DTZ value maps
+    map: usize,
+}
+
+//Decoding information for one part of a table. Offsets point into the table's data.
+#[derive(Clone, Default)]
+struct Pairs {
+    flags: u8,
+    max_sym_len: u8,
+    min_sym_len: u8,
+    num_blocks: usize,
+    block_size: usize,
+    span: usize,
+    lowest_sym: usize,
+    btree: usize,
+    block_length: usize,
+    block_length_size: usize,
+    sparse_index: usize,
+    sparse_index_size: usize,
+    blocks: usize,
+    //base64[length - min_sym_len] is the lowest symbol of that length, padded to 64 bits
+    base64: Vec<u64>,
+    //How many values each symbol stands for, minus one
+    symlen: Vec<u8>,
+    //Piece codes in the order they are indexed: 1-6 white pawn to king, 9-14 black
+    pieces: [u8; MAX_PIECES],
+    group_idx: [u64; MAX_PIECES + 1],
+    group_len: [usize; MAX_PIECES + 1],
+    //Start of the maps for wins, losses, cursed wins and blessed losses
+    map_idx: [u16; 4],
+}
+
+//Lookup tables for turning piece placements into table indices
+struct Indices {
+    map_pawns: [usize; 64],
+    map_b1h1h7: [usize; 64],
+    map_a1d1d4: [usize; 64],
+    map_kk: [[u64; 64]; 10],
+    binomial: [[u64; 64]; MAX_PIECES],
+    lead_pawn_idx: [[u64; 64]; MAX_PIECES],
+    lead_pawns_size: [[u64; 4]; MAX_PIECES],
+}
+
+//The outcome of looking a position up in a single table
+enum Lookup {
+    Value(i32),
+    //DTZ tables only store one side to move, and this position has the other
+    ChangeSide,
+}
+
+impl Wdl {
+    fn from_value(value: i32) -> Option<Self> {
+        return match value {
+            -2 => Some(Wdl::Loss),
+            -1 => Some(Wdl::BlessedLoss),
+            0 => Some(Wdl::Draw),
+            1 => Some(Wdl::CursedWin),
+            2 => Some(Wdl::Win),
+            _ => None
+        };
+    }
+
+    pub fn value(self) -> i32 {
+        return self as i32 - 2;
+    }
+
+    pub fn signum(self) -> i32 {
+        return self.value().signum();
+    }
+}
+
+impl Neg for Wdl {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        return Wdl::from_value(-self.value()).unwrap();
+    }
+}
+
+impl Material {
+    //Reads a table name like KRPvKR
+    fn parse(name: &str) -> Option<Self> {
+        let (strong, weak) = name.split_once('v')?;
+        let mut counts = [[0usize; 6]; 2];
+        for (side, pieces) in [strong, weak].iter().enumerate() {
+            if !pieces.starts_with('K') {
+                return None;
+            }
+            for character in pieces.chars() {
+                let piece = "PNBRQK".find(character)?;
+                counts[side][piece] += 1;
+            }
+            if counts[side][Pieces::King as usize] != 1 {
+                return None;
+            }
+        }
+
+        let piece_count = strong.len() + weak.len();
+        let pawns = [counts[0][Pieces::Pawn as usize], counts[1][Pieces::Pawn as usize]];
+        //The side with fewer pawns leads, since that compresses better
+        let white_leads = pawns[1] == 0 || (pawns[0] > 0 && pawns[1] >= pawns[0]);
+        if piece_count > MAX_PIECES {
+            return None;
+        }
+        return Some(Self {
+            piece_count,
+            has_pawns: pawns[0] + pawns[1] > 0,
+            has_unique_pieces: counts.iter().any(|side| side[..5].contains(&1)),
+            pawn_count: if white_leads { pawns } else { [pawns[1], pawns[0]] },
+            symmetric: strong == weak,
+        });
+    }
+}
+
+impl LazyTable {
+    fn new(path: PathBuf) -> Self {
+        return Self { path, table: OnceLock::new() };
+    }
+
+    fn get(&self, material: &Material, dtz: bool) -> Option<&Table> {
+        return self.table.get_or_init(|| Table::load(&self.path, material, dtz)).as_ref();
+    }
+}
+
+impl Tablebase {
+    //Finds every table in the directory. A material combination is usable once its WDL file is present,
+    //and DTZ probes also need its DTZ file.
+    pub fn open(directory: &str) -> Result<Self, String> {
+        let entries = fs::read_dir(directory).map_err(|error| format!("Failed to read {}: {}", directory, error))?;
+        let mut tablebase = Self {
+            tables: HashMap::new(),
+            max_pieces: 0,
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some("rtbw") {
+                continue;
+            }
+            let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(name) => name.to_string(),
+                None => continue
+            };
+            let material = match Material::parse(&name) {
+                Some(material) => material,
+                None => continue
+            };
+
+            tablebase.max_pieces = tablebase.max_pieces.max(material.piece_count);
+            tablebase.tables.insert(name, TableFiles {
+                material,
+                wdl: LazyTable::new(path.clone()),
+                dtz: LazyTable::new(path.with_extension("rtbz")),
+            });
+        }
+        return Ok(tablebase);
+    }
+
+    //Number of material combinations found
+    pub fn len(&self) -> usize {
+        return self.tables.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.tables.is_empty();
+    }
+
+    //The most pieces, kings included, of any table found
+    pub fn max_pieces(&self) -> usize {
+        return self.max_pieces;
+    }
+
+    //Whether the position can be probed, as far as can be told without reading any tables
+    pub fn can_probe(&self, board: &ChessBoard) -> bool {
+        //Tablebases don't have castling
+        return board.board.0[12].count_ones() as usize <= self.max_pieces && !board.castle_status.contains(&true);
+    }
+
+    //The result with best play, or None if a table that's needed is missing
+    pub fn probe_wdl(&self, board: &ChessBoard) -> Option<Wdl> {
+        if !self.can_probe(board) {
+            return None;
+        }
+        return self.search(board, false).map(|(wdl, _)| wdl);
+    }
+
+    //Plies to the next capture or pawn move with best play, positive when winning, negative when losing
+    //and 0 for draws. Results of 100 or more are cursed wins or blessed losses.
+    pub fn probe_dtz(&self, board: &ChessBoard) -> Option<i32> {
+        if !self.can_probe(board) {
+            return None;
+        }
+
+        let (wdl, zeroing) = self.search(board, true)?;
+        if wdl == Wdl::Draw {
+            return Some(0);
+        }
+        //The table doesn't hold useful values when the best move is a capture or pawn move
+        if zeroing {
+            return Some(dtz_before_zeroing(wdl));
+        }
+
+        match self.lookup(board, Some(wdl))? {
+            Lookup::Value(dtz) => {
+                let cursed = wdl == Wdl::CursedWin || wdl == Wdl::BlessedLoss;
+                return Some((dtz + if cursed { 100 } else { 0 }) * wdl.signum());
+            }
+            Lookup::ChangeSide => {}
+        }
+
+        //Only the other side to move is stored, so look one ply ahead for the best move
+        let mut best = i32::MAX;
+        for chess_move in board.legal_moves() {
+            let zeroing = chess_move.piece == Pieces::Pawn || captured_piece(board, &chess_move).is_some();
+            let mut next = board.clone();
+            next.make_move(&chess_move);
+
+            let mut dtz = if zeroing {
+                -dtz_before_zeroing(self.search(&next, false)?.0)
+            } else {
+                -self.probe_dtz(&next)?
+            };
+            if dtz == 1 && next.in_check() && next.legal_moves().is_empty() {
+                best = 1;
+            }
+            if !zeroing {
+                dtz += dtz.signum();
+            }
+            if dtz < best && dtz.signum() == wdl.signum() {
+                best = dtz;
+            }
+        }
+
+        //No legal moves means mate
+        return Some(if best == i32::MAX { -1 } else { best });
+    }
+
+    //Every legal move with its result, best first. Moves that win before the fifty move rule can step in
+    //come first, fastest first, and losing moves put off the loss as long as possible.
+    pub fn root_moves(&self, board: &ChessBoard) -> Option<Vec<RootMove>> {
+        if !self.can_probe(board) {
+            return None;
+        }
+
+        let halfmoves = 100 - board.fifty_move_counter as i32;
+        let repeated = board.is_repetition();
+        let mut output = Vec::new();
+        for chess_move in board.legal_moves() {
+            let mut next = board.clone();
+            next.make_move(&chess_move);
+
+            let mut dtz = if next.fifty_move_counter == 100 {
+                dtz_before_zeroing(-self.probe_wdl(&next)?)
+            } else if next.fifty_move_counter == 0 || next.repetitions() >= 3 {
+                0
+            } else {
+                let dtz = -self.probe_dtz(&next)?;
+                dtz + dtz.signum()
+            };
+            if dtz == 2 && next.in_check() && next.legal_moves().is_empty() {
+                dtz = 1;
+            }
+
+            let rank = if dtz > 0 {
+                if dtz + halfmoves <= 99 && !repeated { MAX_DTZ } else { MAX_DTZ - (dtz + halfmoves) }
+            } else if dtz < 0 {
+                if -dtz * 2 + halfmoves < 100 { -MAX_DTZ } else { -MAX_DTZ + (-dtz + halfmoves) }
+            } else {
+                0
+            };
+            let wdl = if dtz > 0 {
+                if dtz + halfmoves <= 100 { Wdl::Win } else { Wdl::CursedWin }
+            } else if dtz < 0 {
+                if -dtz + halfmoves <= 100 { Wdl::Loss } else { Wdl::BlessedLoss }
+            } else {
+                Wdl::Draw
+            };
+            output.push(RootMove { chess_move, wdl, dtz, rank });
+        }
+
+        output.sort_by_key(|root_move| (-root_move.rank, root_move.dtz));
+        return Some(output);
+    }
+
+    //A move that keeps the tablebase result, or None if the position can't be probed or has no moves
+    pub fn best_move(&self, board: &ChessBoard) -> Option<RootMove> {
+        return self.root_moves(board)?.first().copied();
+    }
+
+    //Tables may store anything for positions where the side to move has a winning capture, so captures
+    //(and pawn moves for DTZ) are searched and the best of those and the stored value is the real result.
+    //Also returns whether the best move is a capture or pawn move.
+    fn search(&self, board: &ChessBoard, check_zeroing: bool) -> Option<(Wdl, bool)> {
+        let moves = board.legal_moves();
+        if moves.is_empty() {
+            return Some((if board.in_check() { Wdl::Loss } else { Wdl::Draw }, false));
+        }
+
+        let mut best = Wdl::Loss;
+        let mut searched = 0;
+        for chess_move in &moves {
+            if captured_piece(board, chess_move).is_none() && (!check_zeroing || chess_move.piece != Pieces::Pawn) {
+                continue;
+            }
+            searched += 1;
+
+            let mut next = board.clone();
+            next.make_move(chess_move);
+            let value = -self.search(&next, false)?.0;
+            if value > best {
+                best = value;
+                if value == Wdl::Win {
+                    return Some((value, true));
+                }
+            }
+        }
+
+        //With every move searched the stored value isn't needed, and might be wrong when en passant is possible
+        let no_more_moves = searched == moves.len();
+        let value = if no_more_moves {
+            best
+        } else {
+            match self.lookup(board, None)? {
+                Lookup::Value(value) => Wdl::from_value(value)?,
+                Lookup::ChangeSide => return None
+            }
+        };
+
+        if best >= value {
+            return Some((best, best > Wdl::Draw || no_more_moves));
+        }
+        return Some((value, false));
+    }
+
+    //Looks the position up in its WDL table, or its DTZ table when the WDL result is given
+    fn lookup(&self, board: &ChessBoard, wdl: Option<Wdl>) -> Option<Lookup> {
+        if board.board.0[12].count_ones() == 2 {
+            return Some(Lookup::Value(0));
+        }
+
+        let (files, black_stronger) = self.files(board)?;
+        return match wdl {
+            Some(wdl) => files.dtz.get(&files.material, true)?.probe(board, &files.material, black_stronger, Some(wdl)),
+            None => files.wdl.get(&files.material, false)?.probe(board, &files.material, black_stronger, None)
+        };
+    }
+
+    //The table files for the position's material, and whether black is the side stored as white
+    fn files(&self, board: &ChessBoard) -> Option<(&TableFiles, bool)> {
+        let mut names = [String::new(), String::new()];
+        for (index, team) in [Teams::White, Teams::Black].iter().enumerate() {
+            for piece in [Pieces::King, Pieces::Queen, Pieces::Rook, Pieces::Bishop, Pieces::Knight, Pieces::Pawn] {
+                let count = board.board.0[piece as usize + *team as usize].count_ones() as usize;
+                names[index].push_str(&"PNBRQK"[piece as usize..piece as usize + 1].repeat(count));
+            }
+        }
+
+        //Tables are named with the stronger side first and store it as white
+        return match self.tables.get(&format!("{}v{}", names[0], names[1])) {
+            Some(files) => Some((files, false)),
+            None => Some((self.tables.get(&format!("{}v{}", names[1], names[0]))?, true))
+        };
+    }
+}
+
+impl Table {
+    fn load(path: &Path, material: &Material, dtz: bool) -> Option<Self> {
+        let data = fs::read(path).ok()?;
+        if data.len() < 5 || data[..4] != if dtz { DTZ_MAGIC } else { WDL_MAGIC } {
+            return None;
+        }
+        if (data[4] & 2 != 0) != material.has_pawns {
+            return None;
+        }
+
+        let indices = indices();
+        let sides = if !dtz && !material.symmetric { 2 } else { 1 };
+        let files = if material.has_pawns { 4 } else { 1 };
+        let both_pawns = material.has_pawns && material.pawn_count[1] > 0;
+        let mut pairs = vec![vec![Pairs::default(); files]; sides];
+        let mut offset = 5;
+
+        //The order pieces are indexed in, and the order the groups of pieces are combined in
+        for file in 0..files {
+            let first = *data.get(offset)? as usize;
+            let second = if both_pawns { *data.get(offset + 1)? as usize } else { 0xFF };
+            let orders = [[first & 0xF, second & 0xF], [first >> 4, second >> 4]];
+            offset += 1 + both_pawns as usize;
+
+            for piece in 0..material.piece_count {
+                let code = *data.get(offset)?;
+                for (side, side_pairs) in pairs.iter_mut().enumerate() {
+                    side_pairs[file].pieces[piece] = if side == 1 { code >> 4 } else { code & 0xF };
+                }
+                offset += 1;
+            }
+            for (side, side_pairs) in pairs.iter_mut().enumerate() {
+                side_pairs[file].set_groups(material, orders[side], file, indices);
+            }
+        }
+        offset += offset & 1;
+
+        for file in 0..files {
+            for side_pairs in pairs.iter_mut() {
+                offset = side_pairs[file].set_sizes(&data, offset)?;
+            }
+        }
+
+        let map = offset;
+        if dtz {
+            for file in 0..files {
+                let part = &mut pairs[0][file];
+                if part.flags & MAPPED == 0 {
+                    continue;
+                }
+                if part.flags & WIDE != 0 {
+                    offset += offset & 1;
+                    for index in 0..4 {
+                        part.map_idx[index] = ((offset - map) / 2 + 1) as u16;
+                        offset += 2 * read_u16_le(&data, offset)? as usize + 2;
+                    }
+                } else {
+                    for index in 0..4 {
+                        part.map_idx[index] = (offset - map + 1) as u16;
+                        offset += *data.get(offset)? as usize + 1;
+                    }
+                }
+            }
+            offset += offset & 1;
+        }
+
+        for file in 0..files {
+            for side_pairs in pairs.iter_mut() {
+                side_pairs[file].sparse_index = offset;
+                offset += side_pairs[file].sparse_index_size * 6;
+            }
+        }
+        for file in 0..files {
+            for side_pairs in pairs.iter_mut() {
+                side_pairs[file].block_length = offset;
+                offset += side_pairs[file].block_length_size * 2;
+            }
+        }
+        for file in 0..files {
+            for side_pairs in pairs.iter_mut() {
+                offset = (offset + 0x3F) & !0x3F;
+                side_pairs[file].blocks = offset;
+                offset += side_pairs[file].num_blocks * side_pairs[file].block_size;
+            }
+        }
+        if offset > data.len() {
+            return None;
+        }
+
+        return Some(Self { data, pairs, map });
+    }
+
+    fn pairs(&self, side: usize, file: usize) -> &Pairs {
+        let side_pairs = &self.pairs[side % self.pairs.len()];
+        return &side_pairs[file.min(side_pairs.len() - 1)];
+    }
+
+    fn probe(&self, board: &ChessBoard, material: &Material, black_stronger: bool, wdl: Option<Wdl>) -> Option<Lookup> {
+        let indices = indices();
+        let team = board.side_to_move();
+
+        //Symmetric tables only store white to move, and tables store the stronger side as white,
+        //so those positions get their colours swapped and board flipped
+        let flip = black_stronger || (material.symmetric && team == Teams::Black);
+        let flip_color = if flip { 8 } else { 0 };
+        let flip_squares = if flip { 56 } else { 0 };
+        let side = flip as usize ^ team.index();
+
+        let mut squares = [0usize; MAX_PIECES];
+        let mut pieces = [0u8; MAX_PIECES];
+        let mut size = 0;
+        let mut lead_pawns_count = 0;
+        let mut lead_pawns = 0;
+        let mut file = 0;
+
+        //Tables with pawns are split by the file of the leading pawn: the one nearest the edge, then the lowest
+        if material.has_pawns {
+            let lead = self.pairs(0, 0).pieces[0] ^ flip_color;
+            let lead_team = if lead & 8 == 0 { Teams::White } else { Teams::Black };
+            lead_pawns = board.board.0[Pieces::Pawn as usize + lead_team as usize];
+            let mut remaining = lead_pawns;
+            while remaining != 0 {
+                squares[size] = remaining.trailing_zeros() as usize ^ flip_squares;
+                size += 1;
+                remaining &= remaining - 1;
+            }
+            lead_pawns_count = size;
+
+            let leader = (0..lead_pawns_count).max_by_key(|index| indices.map_pawns[squares[*index]]).unwrap();
+            squares.swap(0, leader);
+            file = (squares[0] % 8).min(7 - squares[0] % 8);
+        }
+
+        if wdl.is_some() {
+            let flags = self.pairs(side, file).flags;
+            if (flags & STM) as usize != side && !(material.symmetric && !material.has_pawns) {
+                return Some(Lookup::ChangeSide);
+            }
+        }
+
+        for square in board.board.occupied() & !Bitboard(lead_pawns) {
+            let (piece, piece_team) = board.piece_at(square)?;
+            squares[size] = square.index() ^ flip_squares;
+            pieces[size] = (piece as u8 + 1 + if piece_team == Teams::Black { 8 } else { 0 }) ^ flip_color;
+            size += 1;
+        }
+
+        //Put the pieces in the order the table indexes them
+        let pairs = self.pairs(side, file);
+        for i in lead_pawns_count..size - 1 {
+            for j in i + 1..size {
+                if pairs.pieces[i] == pieces[j] {
+                    pieces.swap(i, j);
+                    squares.swap(i, j);
+                    break;
+                }
+            }
+        }
+
+        //Mirror so the leading piece is on files a to d
+        if squares[0] % 8 > 3 {
+            for square in squares[..size].iter_mut() {
+                *square ^= 7;
+            }
+        }
+
+        let mut index;
+        if material.has_pawns {
+            index = indices.lead_pawn_idx[lead_pawns_count][squares[0]];
+            squares[1..lead_pawns_count].sort_by_key(|square| indices.map_pawns[*square]);
+            for i in 1..lead_pawns_count {
+                index += indices.binomial[i][indices.map_pawns[squares[i]]];
+            }
+        } else {
+            //Without pawns the board can also be flipped so the leading piece is in the a1-d1-d4 triangle
+            if squares[0] / 8 > 3 {
+                for square in squares[..size].iter_mut() {
+                    *square ^= 56;
+                }
+            }
+            for i in 0..pairs.group_len[0] {
+                let offset = off_diagonal(squares[i]);
+                if offset == 0 {
+                    continue;
+                }
+                if offset > 0 {
+                    for square in squares[i..size].iter_mut() {
+                        *square = ((*square >> 3) | (*square << 3)) & 63;
+                    }
+                }
+                break;
+            }
+
+            if material.has_unique_pieces {
+                //The first three pieces are indexed together
+                let (first, second, third) = (squares[0], squares[1], squares[2]);
+                let adjust1 = (second > first) as usize;
+                let adjust2 = (third > first) as usize + (third > second) as usize;
+                index = if off_diagonal(first) != 0 {
+                    ((indices.map_a1d1d4[first] * 63 + second - adjust1) * 62 + third - adjust2) as u64
+                } else if off_diagonal(second) != 0 {
+                    ((6 * 63 + (first / 8) * 28 + indices.map_b1h1h7[second]) * 62 + third - adjust2) as u64
+                } else if off_diagonal(third) != 0 {
+                    (6 * 63 * 62 + 4 * 28 * 62 + (first / 8) * 7 * 28 + (second / 8 - adjust1) * 28 +
+                        indices.map_b1h1h7[third]) as u64
+                } else {
+                    (6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + (first / 8) * 7 * 6 + (second / 8 - adjust1) * 6 +
+                        (third / 8 - adjust2)) as u64
+                };
+            } else {
+                index = indices.map_kk[indices.map_a1d1d4[squares[0]]][squares[1]];
+            }
+        }
+
+        //Then each remaining group, skipping squares taken by earlier groups
+        index *= pairs.group_idx[0];
+        let mut start = pairs.group_len[0];
+        let mut remaining_pawns = material.has_pawns && material.pawn_count[1] > 0;
+        let mut group = 1;
+        while pairs.group_len[group] != 0 {
+            let length = pairs.group_len[group];
+            squares[start..start + length].sort();
+            let mut group_index = 0;
+            for i in 0..length {
+                let square = squares[start + i];
+                let adjust = squares[..start].iter().filter(|earlier| square > **earlier).count();
+                group_index += indices.binomial[i + 1][square - adjust - if remaining_pawns { 8 } else { 0 }];
+            }
+            remaining_pawns = false;
+            index += group_index * pairs.group_idx[group];
+            start += length;
+            group += 1;
+        }
+
+        let value = pairs.decompress(&self.data, index)?;
+        return match wdl {
+            None => Some(Lookup::Value(value - 2)),
+            Some(wdl) => Some(Lookup::Value(self.map_score(pairs, value, wdl)?))
+        };
+    }
+
+    //Turns a stored DTZ value into plies
+    fn map_score(&self, pairs: &Pairs, value: i32, wdl: Wdl) -> Option<i32> {
+        let mut value = value;
+        if pairs.flags & MAPPED != 0 {
+            let map = match wdl {
+                Wdl::Win => 0,
+                Wdl::Loss => 1,
+                Wdl::CursedWin => 2,
+                Wdl::BlessedLoss => 3,
+                Wdl::Draw => 0
+            };
+            let index = pairs.map_idx[map] as usize + value as usize;
+            value = if pairs.flags & WIDE != 0 {
+                read_u16_le(&self.data, self.map + 2 * index)? as i32
+            } else {
+                *self.data.get(self.map + index)? as i32
+            };
+        }
+
+        //Values are stored in moves unless the table says plies
+        if (wdl == Wdl::Win && pairs.flags & WIN_PLIES == 0) || (wdl == Wdl::Loss && pairs.flags & LOSS_PLIES == 0) ||
+            wdl == Wdl::CursedWin || wdl == Wdl::BlessedLoss {
+            value *= 2;
+        }
+        return Some(value + 1);
+    }
+}
+
+impl Pairs {
+    //Splits the pieces into groups of the same piece, and works out what each group's index is multiplied by
+    fn set_groups(&mut self, material: &Material, order: [usize; 2], file: usize, indices: &Indices) {
+        let mut first_length: i32 = if material.has_pawns { 0 } else if material.has_unique_pieces { 3 } else { 2 };
+        let mut groups = 0;
+        self.group_len[0] = 1;
+        for i in 1..material.piece_count {
+            first_length -= 1;
+            if first_length > 0 || self.pieces[i] == self.pieces[i - 1] {
+                self.group_len[groups] += 1;
+            } else {
+                groups += 1;
+                self.group_len[groups] = 1;
+            }
+        }
+        groups += 1;
+        self.group_len[groups] = 0;
+
+        let both_pawns = material.has_pawns && material.pawn_count[1] > 0;
+        let mut next = if both_pawns { 2 } else { 1 };
+        let mut free_squares = 64 - self.group_len[0] - if both_pawns { self.group_len[1] } else { 0 };
+        let mut index = 1u64;
+        let mut k = 0;
+        while next < groups || k == order[0] || k == order[1] {
+            if k == order[0] {
+                self.group_idx[0] = index;
+                index *= if material.has_pawns {
+                    indices.lead_pawns_size[self.group_len[0]][file]
+                } else if material.has_unique_pieces {
+                    31332
+                } else {
+                    462
+                };
+            } else if k == order[1] {
+                self.group_idx[1] = index;
+                index *= indices.binomial[self.group_len[1]][48 - self.group_len[0]];
+            } else {
+                self.group_idx[next] = index;
+                index *= indices.binomial[self.group_len[next]][free_squares];
+                free_squares -= self.group_len[next];
+                next += 1;
+            }
+            k += 1;
+        }
+        self.group_idx[groups] = index;
+    }
+
+    //Reads the compression parameters and symbol tree, returning the offset after them
+    fn set_sizes(&mut self, data: &[u8], offset: usize) -> Option<usize> {
+        let mut offset = offset;
+        self.flags = *data.get(offset)?;
+        offset += 1;
+        if self.flags & SINGLE_VALUE != 0 {
+            //The value is kept in min_sym_len
+            self.min_sym_len = *data.get(offset)?;
+            return Some(offset + 1);
+        }
+
+        let groups = self.group_len.iter().position(|length| *length == 0).unwrap_or(MAX_PIECES);
+        let size = self.group_idx[groups] as usize;
+        self.block_size = 1 << *data.get(offset)?;
+        self.span = 1 << *data.get(offset + 1)?;
+        self.sparse_index_size = (size + self.span - 1) / self.span;
+        let padding = *data.get(offset + 2)? as usize;
+        self.num_blocks = read_u32_le(data, offset + 3)? as usize;
+        self.block_length_size = self.num_blocks + padding;
+        self.max_sym_len = *data.get(offset + 7)?;
+        self.min_sym_len = *data.get(offset + 8)?;
+        offset += 9;
+        if self.min_sym_len == 0 || self.max_sym_len < self.min_sym_len {
+            return None;
+        }
+
+        //Canonical Huffman codes: longer symbols have lower values, so each length's lowest symbol
+        //padded to 64 bits gives the ranges to look symbols up in
+        self.lowest_sym = offset;
+        let lengths = (self.max_sym_len - self.min_sym_len + 1) as usize;
+        self.base64 = vec![0; lengths];
+        for i in (0..lengths - 1).rev() {
+            let lowest = read_u16_le(data, offset + i * 2)? as u64;
+            let next_lowest = read_u16_le(data, offset + (i + 1) * 2)? as u64;
+            self.base64[i] = self.base64[i + 1].wrapping_add(lowest).wrapping_sub(next_lowest) / 2;
+        }
+        for i in 0..lengths {
+            self.base64[i] = self.base64[i].checked_shl(64 - i as u32 - self.min_sym_len as u32).unwrap_or(0);
+        }
+        offset += lengths * 2;
+
+        let symbols = read_u16_le(data, offset)? as usize;
+        offset += 2;
+        self.btree = offset;
+        if offset + symbols * 3 > data.len() {
+            return None;
+        }
+
+        //Each symbol stands for a pair of smaller symbols, down to single values
+        self.symlen = vec![0; symbols];
+        let mut visited = vec![false; symbols];
+        for symbol in 0..symbols {
+            if !visited[symbol] {
+                self.symlen[symbol] = self.set_symlen(data, symbol, &mut visited)?;
+            }
+        }
+        return Some(offset + symbols * 3 + (symbols & 1));
+    }
+
+    fn set_symlen(&mut self, data: &[u8], symbol: usize, visited: &mut Vec<bool>) -> Option<u8> {
+        visited[symbol] = true;
+        let (left, right) = self.children(data, symbol);
+        if right == 0xFFF {
+            return Some(0);
+        }
+        if left >= self.symlen.len() || right >= self.symlen.len() {
+            return None;
+        }
+        if !visited[left] {
+            self.symlen[left] = self.set_symlen(data, left, visited)?;
+        }
+        if !visited[right] {
+            self.symlen[right] = self.set_symlen(data, right, visited)?;
+        }
+        return Some(self.symlen[left].wrapping_add(self.symlen[right]).wrapping_add(1));
+    }
+
+    //The two symbols a symbol expands to, 12 bits each. For single values the left is the value.
+    #[inline]
+    fn children(&self, data: &[u8], symbol: usize) -> (usize, usize) {
+        let bytes = &data[self.btree + symbol * 3..self.btree + symbol * 3 + 3];
+        let left = ((bytes[1] as usize & 0xF) << 8) | bytes[0] as usize;
+        let right = ((bytes[2] as usize) << 4) | (bytes[1] as usize >> 4);
+        return (left, right);
+    }
+
+    #[inline]
+    fn block_length(&self, data: &[u8], block: usize) -> Option<i64> {
+        return read_u16_le(data, self.block_length + block * 2).map(|length| length as i64);
+    }
+
+    //Finds the value at the index
+    fn decompress(&self, data: &[u8], index: u64) -> Option<i32> {
+        if self.flags & SINGLE_VALUE != 0 {
+            return Some(self.min_sym_len as i32);
+        }
+
+        //The sparse index gives a block and an offset for every span values, starting half a span in
+        let entry = self.sparse_index + (index / self.span as u64) as usize * 6;
+        let mut block = read_u32_le(data, entry)? as usize;
+        let mut offset = read_u16_le(data, entry + 4)? as i64;
+        offset += (index % self.span as u64) as i64 - (self.span / 2) as i64;
+
+        //Each block holds its length plus one values
+        while offset < 0 {
+            block = block.checked_sub(1)?;
+            offset += self.block_length(data, block)? + 1;
+        }
+        while offset > self.block_length(data, block)? {
+            offset -= self.block_length(data, block)? + 1;
+            block += 1;
+        }
+
+        //Read symbols until reaching the one that contains the offset
+        let mut pointer = self.blocks + block * self.block_size;
+        let mut buffer = u64::from_be_bytes(data.get(pointer..pointer + 8)?.try_into().unwrap());
+        pointer += 8;
+        let mut buffer_size = 64;
+        let mut symbol;
+        loop {
+            let mut length = 0;
+            while buffer < *self.base64.get(length)? {
+                length += 1;
+            }
+            symbol = ((buffer - self.base64[length]) >> (64 - length - self.min_sym_len as usize)) as u16;
+            symbol = symbol.wrapping_add(read_u16_le(data, self.lowest_sym + length * 2)?);
+            let count = *self.symlen.get(symbol as usize)? as i64 + 1;
+            if offset < count {
+                break;
+            }
+
+            offset -= count;
+            length += self.min_sym_len as usize;
+            buffer <<= length;
+            buffer_size -= length;
+            if buffer_size <= 32 {
+                buffer_size += 32;
+                let next = data.get(pointer..pointer + 4).map_or(0, |bytes| u32::from_be_bytes(bytes.try_into().unwrap()));
+                buffer |= (next as u64) << (64 - buffer_size);
+                pointer += 4;
+            }
+        }
+
+        //Expand the symbol's pairs down to the single value at the offset
+        let mut symbol = symbol as usize;
+        while self.symlen[symbol] != 0 {
+            let (left, right) = self.children(data, symbol);
+            let count = *self.symlen.get(left)? as i64 + 1;
+            if offset < count {
+                symbol = left;
+            } else {
+                offset -= count;
+                symbol = right;
+            }
+            if symbol >= self.symlen.len() {
+                return None;
+            }
+        }
+        return Some(self.children(data, symbol).0 as i32);
+    }
+}
+
+impl Indices {
+    fn new() -> Self {
+        let mut indices = Self {
+            map_pawns: [0; 64],
+            map_b1h1h7: [0; 64],
+            map_a1d1d4: [0; 64],
+            map_kk: [[0; 64]; 10],
+            binomial: [[0; 64]; MAX_PIECES],
+            lead_pawn_idx: [[0; 64]; MAX_PIECES],
+            lead_pawns_size: [[0; 4]; MAX_PIECES],
+        };
+
+        //Squares below the a1-h8 diagonal to 0-27
+        let mut code = 0;
+        for square in 0..64 {
+            if off_diagonal(square) < 0 {
+                indices.map_b1h1h7[square] = code;
+                code += 1;
+            }
+        }
+
+        //The a1-d1-d4 triangle to 0-9, with the diagonal last
+        let mut diagonal = Vec::new();
+        code = 0;
+        for square in 0..28 {
+            if off_diagonal(square) < 0 && square % 8 <= 3 {
+                indices.map_a1d1d4[square] = code;
+                code += 1;
+            } else if off_diagonal(square) == 0 && square % 8 <= 3 {
+                diagonal.push(square);
+            }
+        }
+        for square in diagonal {
+            indices.map_a1d1d4[square] = code;
+            code += 1;
+        }
+
+        //The 462 legal placements of two kings with the first in the triangle.
+        //When the first is on the diagonal, the second can't be above it.
+        let mut both_on_diagonal = Vec::new();
+        let mut code = 0;
+        for index in 0..10 {
+            for first in 0..28 {
+                if indices.map_a1d1d4[first] != index || (index == 0 && first != 1) {
+                    continue;
+                }
+                for second in 0..64 {
+                    if (king_attacks(Square(first as u8)) | Square(first as u8).bitboard()).contains(Square(second as u8)) {
+                        continue;
+                    }
+                    if off_diagonal(first) == 0 && off_diagonal(second) > 0 {
+                        continue;
+                    }
+                    if off_diagonal(first) == 0 && off_diagonal(second) == 0 {
+                        both_on_diagonal.push((index, second));
+                    } else {
+                        indices.map_kk[index][second] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (index, square) in both_on_diagonal {
+            indices.map_kk[index][square] = code;
+            code += 1;
+        }
+
+        indices.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..MAX_PIECES.min(n + 1) {
+                indices.binomial[k][n] = if k > 0 { indices.binomial[k - 1][n - 1] } else { 0 } +
+                    if k < n { indices.binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        //Pawn squares to 0-47, highest nearest the edge and then lowest rank, which makes that pawn lead
+        let mut available = 47;
+        for lead in 1..=5 {
+            for file in 0..4 {
+                let mut index = 0;
+                for rank in 1..7 {
+                    let square = rank * 8 + file;
+                    if lead == 1 {
+                        indices.map_pawns[square] = available;
+                        indices.map_pawns[square ^ 7] = available.saturating_sub(1);
+                        available = available.saturating_sub(2);
+                    }
+                    indices.lead_pawn_idx[lead][square] = index;
+                    index += indices.binomial[lead - 1][indices.map_pawns[square]];
+                }
+                indices.lead_pawns_size[lead][file] = index;
+            }
+        }
+        return indices;
+    }
+}
+
+fn indices() -> &'static Indices {
+    return INDICES.get_or_init(Indices::new);
+}
+
+//How far above the a1-h8 diagonal a square is
+#[inline]
+fn off_diagonal(square: usize) -> i32 {
+    return (square / 8) as i32 - (square % 8) as i32;
+}
+
+//The DTZ of a capture or pawn move, which tables don't store
+fn dtz_before_zeroing(wdl: Wdl) -> i32 {
+    return match wdl {
+        Wdl::Win => 1,
+        Wdl::CursedWin => 101,
+        Wdl::BlessedLoss => -101,
+        Wdl::Loss => -1,
+        Wdl::Draw => 0
+    };
+}
+
+#[inline]
+fn read_u16_le(data: &[u8], offset: usize) -> Option<u16> {
+    return data.get(offset..offset + 2).map(|bytes| u16::from_le_bytes(bytes.try_into().unwrap()));
+}
+
+#[inline]
+fn read_u32_le(data: &[u8], offset: usize) -> Option<u32> {
+    return data.get(offset..offset + 4).map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //A tablebase that knows the names but has no files behind them
+    fn tablebase(names: &[&str]) -> Tablebase {
+        let mut tablebase = Tablebase {
+            tables: HashMap::new(),
+            max_pieces: 0,
+        };
+        for name in names {
+            let material = Material::parse(name).unwrap();
+            tablebase.max_pieces = tablebase.max_pieces.max(material.piece_count);
+            tablebase.tables.insert(name.to_string(), TableFiles {
+                material,
+                wdl: LazyTable::new(PathBuf::from(format!("{}.rtbw", name))),
+                dtz: LazyTable::new(PathBuf::from(format!("{}.rtbz", name))),
+            });
+        }
+        return tablebase;
+    }
+
+    fn stronger_side(tablebase: &Tablebase, fen: &str) -> Option<bool> {
+        return tablebase.files(&ChessBoard::from_fen(fen).unwrap()).map(|(_, black_stronger)| black_stronger);
+    }
+
+    //Checked against what Stockfish's tbprobe.cpp builds for LeadPawnIdx and LeadPawnsSize
+    #[test]
+    fn lead_pawn_indices() {
+        let indices = indices();
+        let sizes: [[u64; 4]; 5] = [
+            [6, 6, 6, 6],
+            [252, 180, 108, 36],
+            [5201, 2645, 953, 125],
+            [70315, 25375, 5491, 295],
+            [700336, 178696, 23176, 496],
+        ];
+        let a_file: [[u64; 6]; 5] = [
+            [0, 1, 2, 3, 4, 5],
+            [0, 47, 92, 135, 176, 215],
+            [0, 1081, 2071, 2974, 3794, 4535],
+            [0, 16215, 30405, 42746, 53406, 62545],
+            [0, 178365, 327360, 450770, 552040, 634291],
+        ];
+        let d_file: [[u64; 6]; 5] = [
+            [0, 1, 2, 3, 4, 5],
+            [0, 11, 20, 27, 32, 35],
+            [0, 55, 91, 112, 122, 125],
+            [0, 165, 249, 284, 294, 295],
+            [0, 330, 456, 491, 496, 496],
+        ];
+        for lead in 1..=5 {
+            assert_eq!(indices.lead_pawns_size[lead], sizes[lead - 1]);
+            for rank in 1..7 {
+                assert_eq!(indices.lead_pawn_idx[lead][rank * 8], a_file[lead - 1][rank - 1]);
+                assert_eq!(indices.lead_pawn_idx[lead][rank * 8 + 3], d_file[lead - 1][rank - 1]);
+            }
+        }
+    }
+
+    #[test]
+    fn king_placements() {
+        let indices = indices();
+        let mut codes: Vec<u64> = indices.map_kk.iter().flatten().copied().collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), 462);
+        assert_eq!(codes.last(), Some(&461));
+        //With the first king on b1, the second starts on d1 and a3 follows h2
+        assert_eq!(indices.map_kk[0][3], 0);
+        assert_eq!(indices.map_kk[0][15], 9);
+        assert_eq!(indices.map_kk[0][16], 10);
+    }
+
+    #[test]
+    fn material_names() {
+        let queen = Material::parse("KQvK").unwrap();
+        assert_eq!(queen.piece_count, 3);
+        assert!(!queen.has_pawns && queen.has_unique_pieces && !queen.symmetric);
+        let pawns = Material::parse("KPPvKP").unwrap();
+        assert!(pawns.has_pawns);
+        assert_eq!(pawns.pawn_count, [1, 2]);
+        assert!(Material::parse("KRvKR").unwrap().symmetric);
+        assert!(Material::parse("KQv").is_none());
+        assert!(Material::parse("KXvK").is_none());
+        assert!(Material::parse("KQQQQQvKR").is_none());
+
+        let tablebase = tablebase(&["KQvK", "KRvKN"]);
+        assert_eq!(stronger_side(&tablebase, "4k3/8/8/8/8/8/8/3QK3 w - - 0 1"), Some(false));
+        assert_eq!(stronger_side(&tablebase, "3qk3/8/8/8/8/8/8/4K3 w - - 0 1"), Some(true));
+        assert_eq!(stronger_side(&tablebase, "3nk3/8/8/8/8/8/8/3RK3 w - - 0 1"), Some(false));
+        assert_eq!(stronger_side(&tablebase, "3rk3/8/8/8/8/8/8/3NK3 b - - 0 1"), Some(true));
+        assert_eq!(stronger_side(&tablebase, "3rk3/8/8/8/8/8/8/3RK3 w - - 0 1"), None);
+    }
+
+    #[test]
+    fn probe_limits() {
+        let tablebase = tablebase(&["KQvK", "KRvKN"]);
+        assert_eq!(tablebase.max_pieces(), 4);
+        assert!(tablebase.can_probe(&ChessBoard::from_fen("3nk3/8/8/8/8/8/8/3RK3 w - - 0 1").unwrap()));
+        assert!(!tablebase.can_probe(&ChessBoard::from_fen("3nk3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap()));
+        assert!(!tablebase.can_probe(&ChessBoard::from_fen("3nk3/8/8/8/8/8/8/2QRK3 w - - 0 1").unwrap()));
+        //Missing tables make the probe fail rather than guess
+        assert!(tablebase.probe_wdl(&ChessBoard::from_fen("3nk3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap()).is_none());
+        assert!(tablebase.probe_wdl(&ChessBoard::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap()).is_none());
+    }
+}
@@ -0,0 +1,192 @@
+use std::fmt::{Display, Formatter};
+use crate::ChessBoard;
+use crate::attacks::{between, piece_attacks};
+use crate::bitboard::{Bitboard, Square};
+use crate::pieces::Pieces;
+use crate::see::DEFAULT_PIECE_VALUES;
+use crate::util::{Move, Teams};
+
+//A tactical pattern, with the squares involved. Motifs are found for one team, exploiting the other.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum Motif {
+    //One piece attacks several enemy pieces that are each the king, worth more than it, or undefended
+    Fork { attacker: Square, targets: Bitboard },
+    //The pinned piece can't move off the line without exposing the more valuable piece behind it.
+    //Absolute when that piece is the king, so moving off the line is illegal.
+    Pin { pinner: Square, pinned: Square, behind: Square, absolute: bool },
+    //Like a pin, but the more valuable piece is in front and has to move out of the way
+    Skewer { attacker: Square, front: Square, behind: Square },
+    //Moving a piece off the line lets a slider attack the target, which is a discovered check when it's the king
+    DiscoveredAttack { moved: Square, attacker: Square, target: Square, check: bool },
+    //A piece that can be taken for a gain in material
+    HangingPiece { square: Square, attackers: Bitboard },
+    //The only defender of several attacked pieces, so it can't protect them all
+    OverloadedDefender { defender: Square, defended: Bitboard },
+}
+
+impl Motif {
+    //Every square the motif involves, for highlighting
+    pub fn squares(&self) -> Bitboard {
+        return match *self {
+            Motif::Fork { attacker, targets } => targets.with(attacker),
+            Motif::Pin { pinner, pinned, behind, .. } => Bitboard::from_iter([pinner, pinned, behind]),
+            Motif::Skewer { attacker, front, behind } => Bitboard::from_iter([attacker, front, behind]),
+            Motif::DiscoveredAttack { moved, attacker, target, .. } => Bitboard::from_iter([moved, attacker, target]),
+            Motif::HangingPiece { square, attackers } => attackers.with(square),
+            Motif::OverloadedDefender { defender, defended } => defended.with(defender)
+        };
+    }
+}
+
+#[inline]
+fn value(piece: Pieces) -> i32 {
+    return DEFAULT_PIECE_VALUES[piece as usize];
+}
+
+impl ChessBoard {
+    //Motifs the team has against the other team in the position, whoever is to move
+    pub fn motifs(&self, team: Teams) -> Vec<Motif> {
+        let mut output = Vec::new();
+        self.find_forks(team, &mut output);
+        self.find_pins_and_skewers(team, &mut output);
+        self.find_hanging_pieces(team, &mut output);
+        self.find_overloaded_defenders(team, &mut output);
+        return output;
+    }
+
+    //Motifs the move creates for the side making it: discovered attacks, and anything that wasn't there before it
+    pub fn move_motifs(&self, chess_move: &Move) -> Vec<Motif> {
+        let team = chess_move.team;
+        let before = self.motifs(team);
+        let mut next = self.clone();
+        next.make_move(chess_move);
+
+        let mut output = Vec::new();
+        let enemy = next.team_pieces(!team);
+        for piece in [Pieces::Bishop, Pieces::Rook, Pieces::Queen] {
+            for attacker in next.board.get_board(piece, team).without(chess_move.to) {
+                let uncovered = piece_attacks(piece, team, attacker, next.board.occupied()) &
+                    !piece_attacks(piece, team, attacker, self.board.occupied()) & enemy;
+                for target in uncovered {
+                    if between(attacker, target).contains(chess_move.from) {
+                        output.push(Motif::DiscoveredAttack {
+                            moved: chess_move.from,
+                            attacker,
+                            target,
+                            check: next.board.get_board(Pieces::King, !team).contains(target),
+                        });
+                    }
+                }
+            }
+        }
+        output.extend(next.motifs(team).into_iter().filter(|motif| !before.contains(motif)));
+        return output;
+    }
+
+    fn find_forks(&self, team: Teams, output: &mut Vec<Motif>) {
+        let occupied = self.board.occupied();
+        let enemy = self.team_pieces(!team);
+        for piece in [Pieces::Pawn, Pieces::Knight, Pieces::Bishop, Pieces::Rook, Pieces::Queen, Pieces::King] {
+            for attacker in self.board.get_board(piece, team) {
+                let targets: Bitboard = (piece_attacks(piece, team, attacker, occupied) & enemy).squares().filter(|target| match self.piece_at(*target) {
+                    Some((victim, _)) => victim == Pieces::King || value(victim) > value(piece) ||
+                        self.attackers_of(*target, !team).is_empty(),
+                    None => false
+                }).collect();
+                if targets.count() >= 2 {
+                    output.push(Motif::Fork { attacker, targets });
+                }
+            }
+        }
+    }
+
+    fn find_pins_and_skewers(&self, team: Teams, output: &mut Vec<Motif>) {
+        let occupied = self.board.occupied();
+        let enemy = self.team_pieces(!team);
+        for piece in [Pieces::Bishop, Pieces::Rook, Pieces::Queen] {
+            for attacker in self.board.get_board(piece, team) {
+                let attacks = piece_attacks(piece, team, attacker, occupied);
+                for front in attacks & enemy {
+                    //The piece the slider would hit next if the front piece weren't there
+                    let revealed = piece_attacks(piece, team, attacker, occupied.without(front)) & !attacks;
+                    let behind = match (revealed & enemy).first() {
+                        Some(behind) => behind,
+                        None => continue
+                    };
+                    let (front_piece, behind_piece) = match (self.piece_at(front), self.piece_at(behind)) {
+                        (Some((front_piece, _)), Some((behind_piece, _))) => (front_piece, behind_piece),
+                        _ => continue
+                    };
+                    if value(behind_piece) > value(front_piece) {
+                        output.push(Motif::Pin { pinner: attacker, pinned: front, behind, absolute: behind_piece == Pieces::King });
+                    } else if value(front_piece) > value(behind_piece) {
+                        output.push(Motif::Skewer { attacker, front, behind });
+                    }
+                }
+            }
+        }
+    }
+
+    fn find_hanging_pieces(&self, team: Teams, output: &mut Vec<Motif>) {
+        let targets = self.team_pieces(!team) & !self.board.get_board(Pieces::King, !team) & self.attacked_squares(team);
+        for square in targets {
+            let attackers = self.attackers_of(square, team);
+            let wins = attackers.squares().any(|from| match self.piece_at(from) {
+                Some((piece, _)) => self.see(&Move {
+                    piece,
+                    team,
+                    from,
+                    to: square,
+                    promotion: None,
+                }) > 0,
+                None => false
+            });
+            if wins {
+                output.push(Motif::HangingPiece { square, attackers });
+            }
+        }
+    }
+
+    fn find_overloaded_defenders(&self, team: Teams, output: &mut Vec<Motif>) {
+        let targets = self.team_pieces(!team) & !self.board.get_board(Pieces::King, !team) & self.attacked_squares(team);
+        let mut defended = [Bitboard::EMPTY; 64];
+        for square in targets {
+            let defenders = self.attackers_of(square, !team);
+            if let Some(defender) = defenders.first().filter(|_| defenders.is_single()) {
+                defended[defender.index()] = defended[defender.index()].with(square);
+            }
+        }
+        for (index, squares) in defended.iter().enumerate() {
+            if squares.count() >= 2 {
+                output.push(Motif::OverloadedDefender { defender: Square(index as u8), defended: *squares });
+            }
+        }
+    }
+}
+
+impl Display for Motif {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            Motif::Fork { attacker, targets } => {
+                write!(f, "Fork by {} of", attacker)?;
+                for target in *targets {
+                    write!(f, " {}", target)?;
+                }
+                Ok(())
+            }
+            Motif::Pin { pinner, pinned, behind, absolute } => write!(f, "{} pin by {} of {} to {}",
+                if *absolute { "Absolute" } else { "Relative" }, pinner, pinned, behind),
+            Motif::Skewer { attacker, front, behind } => write!(f, "Skewer by {} of {} and {}", attacker, front, behind),
+            Motif::DiscoveredAttack { moved, attacker, target, check } => write!(f, "Discovered {} by {} on {}, uncovered from {}",
+                if *check { "check" } else { "attack" }, attacker, target, moved),
+            Motif::HangingPiece { square, .. } => write!(f, "Hanging piece on {}", square),
+            Motif::OverloadedDefender { defender, defended } => {
+                write!(f, "Overloaded defender on {} guarding", defender)?;
+                for square in *defended {
+                    write!(f, " {}", square)?;
+                }
+                Ok(())
+            }
+        };
+    }
+}
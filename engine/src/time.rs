@@ -0,0 +1,104 @@
+use std::time::Duration;
+use crate::util::Move;
+
+//Moves left to plan for when the clock doesn't say
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+//The hard deadline is this many times the base allocation, so unstable positions can run long
+const HARD_SCALE: u32 = 4;
+//Never plan to spend more than this fraction of the remaining time on one move
+const MAX_FRACTION: f64 = 0.5;
+//With only one legal move, the search just needs to confirm it
+const SINGLE_MOVE_SCALE: f64 = 0.1;
+
+//The clock state for the side to move
+#[derive(Clone, Copy)]
+pub struct Clock {
+    pub remaining: Duration,
+    pub increment: Duration,
+    //Moves until the next time control, or None for sudden death
+    pub moves_to_go: Option<u32>,
+    //Time lost to communication with the server on every move
+    pub move_overhead: Duration,
+}
+
+//Turns clock state into per-move deadlines, and adjusts them as iterations finish
+#[derive(Clone)]
+pub struct TimeManager {
+    //The allocation for an ordinary move
+    base: Duration,
+    //Never think longer than this
+    maximum: Duration,
+    //Weighted count of recent best move changes, decaying each iteration
+    best_move_changes: f64,
+    previous_best: Option<Move>,
+    //How far the score has fallen since the first iteration, in centipawns
+    score_drop: i32,
+    first_score: Option<i32>,
+    single_move: bool,
+}
+
+impl TimeManager {
+    pub fn new(clock: &Clock) -> Self {
+        let available = clock.remaining.saturating_sub(clock.move_overhead);
+        let moves_to_go = clock.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+
+        //Spread the time over the moves left, and spend most of the increment since it comes back
+        let base = available / moves_to_go + clock.increment * 3 / 4;
+        let maximum = (base * HARD_SCALE).min(available.mul_f64(MAX_FRACTION)).min(available);
+
+        return Self {
+            base: base.min(maximum),
+            maximum,
+            best_move_changes: 0.0,
+            previous_best: None,
+            score_drop: 0,
+            first_score: None,
+            single_move: false,
+        };
+    }
+
+    //Call when the side to move has only one legal move
+    pub fn single_legal_move(&mut self) {
+        self.single_move = true;
+    }
+
+    //Call after each finished iteration with its best move and score
+    pub fn update(&mut self, best_move: Option<Move>, score: i32) {
+        self.best_move_changes *= 0.5;
+        if self.previous_best.is_some() && best_move != self.previous_best {
+            self.best_move_changes += 1.0;
+        }
+        self.previous_best = best_move;
+
+        let first = *self.first_score.get_or_insert(score);
+        self.score_drop = (first - score).max(0);
+    }
+
+    //Past this, don't start another iteration
+    pub fn soft_deadline(&self) -> Duration {
+        if self.single_move {
+            return self.base.mul_f64(SINGLE_MOVE_SCALE);
+        }
+
+        //An unstable best move or a falling score both mean the position needs more thought
+        let instability = 1.0 + self.best_move_changes.min(3.0) * 0.5;
+        let falling = 1.0 + (self.score_drop as f64 / 100.0).min(1.0);
+        return self.base.mul_f64(instability * falling).min(self.maximum);
+    }
+
+    //Past this, stop searching immediately
+    pub fn hard_deadline(&self) -> Duration {
+        if self.single_move {
+            return self.base.mul_f64(SINGLE_MOVE_SCALE);
+        }
+        return self.maximum;
+    }
+
+    pub fn should_start_iteration(&self, elapsed: Duration) -> bool {
+        return elapsed < self.soft_deadline();
+    }
+
+    pub fn out_of_time(&self, elapsed: Duration) -> bool {
+        return elapsed >= self.hard_deadline();
+    }
+}
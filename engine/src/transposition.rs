@@ -0,0 +1,238 @@
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use crate::search::MATE_BOUND;
+use crate::compact::CompactMove;
+
+//Entries per bucket. The first is kept for the deepest search, the second is always replaced.
+const BUCKET_SIZE: usize = 2;
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum Bound {
+    //The score is exact
+    Exact,
+    //The search failed high, the real score is at least this
+    Lower,
+    //The search failed low, the real score is at most this
+    Upper,
+}
+
+#[derive(Clone, Copy)]
+pub struct TableEntry {
+    pub key: u64,
+    pub depth: u8,
+    pub bound: Bound,
+    pub score: i32,
+    //The null move when the search didn't find one
+    pub best_move: CompactMove,
+    //The search the entry was stored in, for replacing stale entries
+    pub age: u8,
+}
+
+//Each entry is two words: the key xored with the data, and the data. A torn write from another thread
+//leaves a key that no longer matches, so entries can be shared between threads without locks.
+struct Bucket {
+    entries: [[AtomicU64; 2]; BUCKET_SIZE],
+}
+
+//A hash table of searched positions, shared between searches of the same game and between search threads
+pub struct TranspositionTable {
+    buckets: Vec<Bucket>,
+    age: AtomicU8,
+}
+
+impl Bucket {
+    fn new() -> Self {
+        return Self {
+            entries: [[AtomicU64::new(0), AtomicU64::new(0)], [AtomicU64::new(0), AtomicU64::new(0)]],
+        };
+    }
+
+    #[inline]
+    fn load(&self, slot: usize) -> Option<TableEntry> {
+        let data = self.entries[slot][1].load(Ordering::Relaxed);
+        let key = self.entries[slot][0].load(Ordering::Relaxed) ^ data;
+        return TableEntry::unpack(key, data);
+    }
+
+    #[inline]
+    fn save(&self, slot: usize, entry: &TableEntry) {
+        let data = entry.pack();
+        self.entries[slot][0].store(entry.key ^ data, Ordering::Relaxed);
+        self.entries[slot][1].store(data, Ordering::Relaxed);
+    }
+
+    fn clear(&self) {
+        for entry in &self.entries {
+            entry[0].store(0, Ordering::Relaxed);
+            entry[1].store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+impl TableEntry {
+    //Bits 0-15 score, 16-23 depth, 24-25 bound (0 is empty), 26-33 age and 34-49 the best move
+    fn pack(&self) -> u64 {
+        return (self.score as i16 as u16) as u64 |
+            (self.depth as u64) << 16 |
+            (match self.bound {
+                Bound::Exact => 1u64,
+                Bound::Lower => 2,
+                Bound::Upper => 3
+            }) << 24 |
+            (self.age as u64) << 26 |
+            (self.best_move.0 as u64) << 34;
+    }
+
+    fn unpack(key: u64, data: u64) -> Option<Self> {
+        let bound = match (data >> 24) & 0b11 {
+            1 => Bound::Exact,
+            2 => Bound::Lower,
+            3 => Bound::Upper,
+            _ => return None
+        };
+
+        return Some(Self {
+            key,
+            depth: (data >> 16) as u8,
+            bound,
+            score: data as u16 as i16 as i32,
+            best_move: CompactMove((data >> 34) as u16),
+            age: (data >> 26) as u8,
+        });
+    }
+}
+
+impl TranspositionTable {
+    pub fn new(megabytes: usize) -> Self {
+        let mut table = Self {
+            buckets: Vec::new(),
+            age: AtomicU8::new(0),
+        };
+        table.resize(megabytes);
+        return table;
+    }
+
+    //Resizes the table to fit in the given megabytes, clearing it
+    pub fn resize(&mut self, megabytes: usize) {
+        let buckets = (megabytes * 1024 * 1024 / size_of::<Bucket>()).max(1);
+        self.buckets = (0..buckets).map(|_| Bucket::new()).collect();
+        self.age.store(0, Ordering::Relaxed);
+    }
+
+    pub fn clear(&self) {
+        for bucket in &self.buckets {
+            bucket.clear();
+        }
+        self.age.store(0, Ordering::Relaxed);
+    }
+
+    //Call before each search so entries from older searches get replaced first
+    pub fn new_search(&self) {
+        self.age.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn megabytes(&self) -> usize {
+        return self.buckets.len() * size_of::<Bucket>() / (1024 * 1024);
+    }
+
+    #[inline]
+    fn bucket(&self, key: u64) -> &Bucket {
+        //Multiply instead of modulo to spread keys over any table size
+        return &self.buckets[((key as u128 * self.buckets.len() as u128) >> 64) as usize];
+    }
+
+    //Finds the position, with mate scores adjusted to be relative to the given ply
+    pub fn probe(&self, key: u64, ply: usize) -> Option<TableEntry> {
+        let bucket = self.bucket(key);
+        for slot in 0..BUCKET_SIZE {
+            if let Some(mut entry) = bucket.load(slot) {
+                if entry.key == key {
+                    entry.score = score_from_table(entry.score, ply);
+                    return Some(entry);
+                }
+            }
+        }
+        return None;
+    }
+
+    pub fn store(&self, key: u64, depth: u8, bound: Bound, score: i32, best_move: CompactMove, ply: usize) {
+        let age = self.age.load(Ordering::Relaxed);
+        let bucket = self.bucket(key);
+        let existing = [bucket.load(0), bucket.load(1)];
+
+        //Keep the old best move if this search didn't find one
+        let mut best_move = best_move;
+        for entry in existing.iter().flatten() {
+            if entry.key == key && best_move.is_null() {
+                best_move = entry.best_move;
+            }
+        }
+
+        let entry = TableEntry {
+            key,
+            depth,
+            bound,
+            score: score_to_table(score, ply),
+            best_move,
+            age,
+        };
+
+        //The depth-preferred slot takes the entry if it's deeper, from an older search, or the same position
+        let replace = match &existing[0] {
+            Some(current) => current.key == key || current.age != age || depth >= current.depth,
+            None => true
+        };
+        if replace {
+            //The same position shouldn't also take up the always-replace slot
+            if let Some(current) = existing[1] {
+                if current.key == key {
+                    bucket.entries[1][0].store(0, Ordering::Relaxed);
+                    bucket.entries[1][1].store(0, Ordering::Relaxed);
+                }
+            }
+            bucket.save(0, &entry);
+        } else {
+            bucket.save(1, &entry);
+        }
+    }
+
+    //How full the table is with entries from the current search, in thousandths
+    pub fn hashfull(&self) -> usize {
+        let age = self.age.load(Ordering::Relaxed);
+        let sample = self.buckets.len().min(1000);
+        let mut used = 0;
+        for bucket in &self.buckets[..sample] {
+            for slot in 0..BUCKET_SIZE {
+                if let Some(entry) = bucket.load(slot) {
+                    if entry.age == age {
+                        used += 1;
+                    }
+                }
+            }
+        }
+        return used * 1000 / (sample * BUCKET_SIZE);
+    }
+}
+
+//Mate scores are stored as distance from the stored position instead of from the root
+#[inline]
+pub fn score_to_table(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        return score + ply as i32;
+    }
+    if score <= -MATE_BOUND {
+        return score - ply as i32;
+    }
+    return score;
+}
+
+#[inline]
+pub fn score_from_table(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        return score - ply as i32;
+    }
+    if score <= -MATE_BOUND {
+        return score + ply as i32;
+    }
+    return score;
+}
@@ -1,18 +1,26 @@
 use std::fmt::{Display, Formatter};
 use std::ops::{Not, Range};
 use crate::ChessBoard;
-use crate::pieces::{FIRST_SQUARE, Pieces};
+use crate::bitboard::{Bitboard, Square};
+use crate::pieces::Pieces;
 
 #[derive(Clone, Copy, Eq, PartialEq)]
 pub struct Position(pub u64);
 
-pub struct PiecePositions(pub u64);
-
-pub struct Row(pub u64);
-
 //A bitboard representation of the board, with each piece for each team having a binary board.
+#[derive(Clone, Eq, Hash, PartialEq)]
 pub struct Board(pub [u64; 13]);
 
+//A single move, with the piece a pawn promotes into when it reaches the last row
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct Move {
+    pub piece: Pieces,
+    pub team: Teams,
+    pub from: Square,
+    pub to: Square,
+    pub promotion: Option<Pieces>,
+}
+
 impl From<&str> for Position {
     #[inline]
     fn from(pos: &str) -> Self {
@@ -22,14 +30,14 @@ impl From<&str> for Position {
 
 impl From<(u8, u8)> for Position {
     fn from((x, y): (u8, u8)) -> Self {
-        return Position(0b1 << ((x * 8) + y));
+        return Position(0b1 << ((y * 8) + x));
     }
 }
 
 impl Display for Position {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut index = self.0.clone();
-        for i in 0..63 {
+        for i in 0..64 {
             if index == 1 {
                 return write!(f, "{}{}", (b'a' + i % 8) as char, (b'1' + i / 8) as char);
             }
@@ -40,9 +48,20 @@ impl Display for Position {
     }
 }
 
+//Coordinate notation, like e2e4 or e7e8q
+impl Display for Move {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.from, self.to)?;
+        if let Some(promotion) = self.promotion {
+            write!(f, "{}", promotion.letter(Teams::Black))?;
+        }
+        return Ok(());
+    }
+}
+
 impl Position {
     #[inline]
-    pub fn on_row(&self, row: Row) -> bool {
+    pub fn on_row(&self, row: Bitboard) -> bool {
         return self.0 & row.0 != 0;
     }
 
@@ -69,8 +88,13 @@ impl Position {
 
 impl Board {
     #[inline]
-    pub fn get_board(&self, piece: Pieces, team: Teams) -> PiecePositions {
-        return PiecePositions(self.0[team as usize + piece as usize]);
+    pub fn get_board(&self, piece: Pieces, team: Teams) -> Bitboard {
+        return Bitboard(self.0[team as usize + piece as usize]);
+    }
+
+    #[inline]
+    pub fn occupied(&self) -> Bitboard {
+        return Bitboard(self.0[12]);
     }
 
     #[inline]
@@ -79,19 +103,8 @@ impl Board {
     }
 }
 
-fn is_in_check(board: &ChessBoard, position: &Position, team: Teams) -> bool {
-    for piece in (!team).non_king_pieces() {
-        let piece_enum = Pieces::from(piece as u8);
-        let mut index = Position(FIRST_SQUARE);
-        for _ in 0..64 {
-            if index.is_occupied_by(&board.board, piece_enum, !team) &&
-                piece_enum.get_moves(index, !team, board, true).contains(position) {
-                return true;
-            }
-            index = Directions::East.offset(index);
-        }
-    }
-    return false;
+fn is_in_check(board: &ChessBoard, square: Square, team: Teams) -> bool {
+    return board.is_attacked(square, !team);
 }
 
 #[derive(Clone)]
@@ -122,18 +135,21 @@ impl Directions {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub enum Teams {
     White = 0,
     Black = 6,
 }
 
 impl Teams {
-    pub fn win_status(&self) -> GameStatus {
-        return match self {
-            Teams::White => GameStatus::WhiteWin,
-            Teams::Black => GameStatus::BlackWin
-        };
+    //Index of the team for per-team tables, white first
+    #[inline]
+    pub fn index(&self) -> usize {
+        return *self as usize / 6;
+    }
+
+    pub fn win_status(&self, termination: Termination) -> GameStatus {
+        return GameStatus::win(*self, termination);
     }
 
     pub fn non_king_pieces(&self) -> Range<usize> {
@@ -179,13 +195,113 @@ pub enum CastleDirection {
     Right = 1,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub enum GameStatus {
     Ongoing,
-    BlackWin,
-    WhiteWin,
-    DrawByStalemate,
-    DrawByInsufficientMaterial,
-    DrawByRepetition,
-    DrawByFiftyMoveRule,
+    Finished(GameResult),
+}
+
+//How a game ended, and who won. No winner is a draw.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct GameResult {
+    pub winner: Option<Teams>,
+    pub termination: Termination,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    Resignation,
+    TimeForfeit,
+    IllegalMove,
+    Disconnect,
+    Adjudication,
+    AgreedDraw,
+    Repetition,
+    FiftyMoveRule,
+    SeventyFiveMoveRule,
+    InsufficientMaterial,
+}
+
+impl GameStatus {
+    pub fn win(winner: Teams, termination: Termination) -> Self {
+        return GameStatus::Finished(GameResult { winner: Some(winner), termination });
+    }
+
+    pub fn draw(termination: Termination) -> Self {
+        return GameStatus::Finished(GameResult { winner: None, termination });
+    }
+
+    pub fn is_over(&self) -> bool {
+        return *self != GameStatus::Ongoing;
+    }
+
+    pub fn result(&self) -> Option<GameResult> {
+        return match self {
+            GameStatus::Ongoing => None,
+            GameStatus::Finished(result) => Some(*result)
+        };
+    }
+
+    pub fn winner(&self) -> Option<Teams> {
+        return self.result().and_then(|result| result.winner);
+    }
+
+    pub fn termination(&self) -> Option<Termination> {
+        return self.result().map(|result| result.termination);
+    }
+
+    //The PGN Result tag: 1-0, 0-1, 1/2-1/2, or * for a game still going
+    pub fn pgn_result(&self) -> &'static str {
+        return match self {
+            GameStatus::Ongoing => "*",
+            GameStatus::Finished(result) => match result.winner {
+                Some(Teams::White) => "1-0",
+                Some(Teams::Black) => "0-1",
+                None => "1/2-1/2"
+            }
+        };
+    }
+
+    //The PGN Termination tag, which only separates normal endings from the rest
+    pub fn pgn_termination(&self) -> &'static str {
+        return match self.termination() {
+            None => "unterminated",
+            Some(Termination::TimeForfeit) => "time forfeit",
+            Some(Termination::IllegalMove) => "rules infraction",
+            Some(Termination::Disconnect) => "abandoned",
+            Some(Termination::Adjudication) => "adjudication",
+            Some(_) => "normal"
+        };
+    }
+}
+
+impl Display for GameStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            GameStatus::Ongoing => write!(f, "Ongoing"),
+            GameStatus::Finished(GameResult { winner: Some(winner), termination }) => write!(f, "{} wins by {}", winner, termination),
+            GameStatus::Finished(GameResult { winner: None, termination }) => write!(f, "Draw by {}", termination)
+        };
+    }
+}
+
+impl Display for Termination {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "{}", match self {
+            Termination::Checkmate => "checkmate",
+            Termination::Stalemate => "stalemate",
+            Termination::Resignation => "resignation",
+            Termination::TimeForfeit => "time forfeit",
+            Termination::IllegalMove => "illegal move",
+            Termination::Disconnect => "disconnect",
+            Termination::Adjudication => "adjudication",
+            Termination::AgreedDraw => "agreement",
+            Termination::Repetition => "repetition",
+            Termination::FiftyMoveRule => "the fifty move rule",
+            Termination::SeventyFiveMoveRule => "the seventy-five move rule",
+            Termination::InsufficientMaterial => "insufficient material"
+        });
+    }
 }
\ No newline at end of file
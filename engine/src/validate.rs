@@ -0,0 +1,152 @@
+use std::fmt::{Display, Formatter};
+use crate::ChessBoard;
+use crate::bitboard::{Bitboard, Rank, Square};
+use crate::movegen::{CASTLE_ROOKS, KING_STARTS};
+use crate::pieces::{BOTTOM_ROW, Pieces, TOP_ROW};
+use crate::util::Teams;
+
+//Pieces of each kind a side starts with, for spotting more promotions than there were pawns
+const STARTING_COUNTS: [u32; 6] = [8, 2, 2, 2, 1, 1];
+//The seventy-five move rule ends the game at 150 plies, so a halfmove clock can't go past it
+pub const MAX_HALFMOVE_CLOCK: u8 = 150;
+
+//Something that makes a position impossible to reach or unsafe to search
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum PositionProblem {
+    MissingKing(Teams),
+    TooManyKings(Teams),
+    TooManyPieces(Teams),
+    TooManyPawns(Teams),
+    //More promoted pieces than missing pawns
+    TooManyPromotions(Teams),
+    PawnOnBackRank(Square),
+    //More than one piece board has the square set
+    OverlappingPieces(Square),
+    //board[12] doesn't match the piece boards on the square
+    OccupancyMismatch(Square),
+    //The side that just moved left its king in check
+    OpponentInCheck,
+    //More pieces give check than any move could uncover
+    TooManyCheckers,
+    //The castling right, indexed like castle_status, has no king or rook on its starting square
+    ImpossibleCastling(usize),
+    //last_pawn isn't a pawn that could have just moved two squares
+    ImpossibleEnPassant(Square),
+    FiftyMoveCounterOutOfRange,
+    //Given to PositionBuilder, and over MAX_HALFMOVE_CLOCK
+    HalfmoveClockOutOfRange,
+    //Too large to count the plies of
+    FullmoveNumberOutOfRange,
+}
+
+impl ChessBoard {
+    //Every problem with the position, or an empty list if it's fine
+    pub fn validate(&self) -> Vec<PositionProblem> {
+        let board = &self.board.0;
+        let mut problems = Vec::new();
+
+        //Each square should be on at most one piece board, and on board[12] exactly when it's on one
+        let mut union = 0u64;
+        let mut overlap = 0u64;
+        for pieces in &board[..12] {
+            overlap |= union & pieces;
+            union |= pieces;
+        }
+        for square in Bitboard(overlap) {
+            problems.push(PositionProblem::OverlappingPieces(square));
+        }
+        for square in Bitboard(union ^ board[12]) {
+            problems.push(PositionProblem::OccupancyMismatch(square));
+        }
+
+        for team in [Teams::White, Teams::Black] {
+            let kings = board[Pieces::King as usize + team as usize].count_ones();
+            if kings == 0 {
+                problems.push(PositionProblem::MissingKing(team));
+            } else if kings > 1 {
+                problems.push(PositionProblem::TooManyKings(team));
+            }
+
+            let counts: Vec<u32> = (0..6).map(|piece| board[piece + team as usize].count_ones()).collect();
+            let pawns = counts[Pieces::Pawn as usize];
+            let promotions: u32 = (1..5).map(|piece| counts[piece].saturating_sub(STARTING_COUNTS[piece])).sum();
+            if counts.iter().sum::<u32>() > 16 {
+                problems.push(PositionProblem::TooManyPieces(team));
+            }
+            if pawns > 8 {
+                problems.push(PositionProblem::TooManyPawns(team));
+            } else if pawns + promotions > 8 {
+                problems.push(PositionProblem::TooManyPromotions(team));
+            }
+        }
+
+        let pawns = self.board.get_board(Pieces::Pawn, Teams::White) | self.board.get_board(Pieces::Pawn, Teams::Black);
+        for square in pawns & (TOP_ROW | BOTTOM_ROW) {
+            problems.push(PositionProblem::PawnOnBackRank(square));
+        }
+
+        //Checks only make sense with one king each
+        let team = self.side_to_move();
+        if self.board.get_board(Pieces::King, team).is_single() && self.board.get_board(Pieces::King, !team).is_single() {
+            if self.king_square(!team).is_some_and(|king| self.is_attacked(king, team)) {
+                problems.push(PositionProblem::OpponentInCheck);
+            }
+            if self.checkers().count() > 2 {
+                problems.push(PositionProblem::TooManyCheckers);
+            }
+        }
+
+        for (index, rook) in CASTLE_ROOKS.iter().enumerate() {
+            let castle_team = if index < 2 { Teams::White } else { Teams::Black };
+            if self.castle_status[index] && (!self.board.get_board(Pieces::King, castle_team).contains(KING_STARTS[index / 2]) ||
+                !self.board.get_board(Pieces::Rook, castle_team).contains(*rook)) {
+                problems.push(PositionProblem::ImpossibleCastling(index));
+            }
+        }
+
+        //The pawn that just moved two squares belongs to the side not to move, with the two squares it passed empty
+        if let Some(pawn) = self.last_pawn {
+            let (rank, behind) = match team {
+                Teams::White => (Rank(4), 1),
+                Teams::Black => (Rank(3), -1)
+            };
+            let passed = [pawn.offset(0, behind), pawn.offset(0, 2 * behind)];
+            if pawn.rank() != rank || !self.board.get_board(Pieces::Pawn, !team).contains(pawn) ||
+                passed.iter().flatten().any(|square| self.board.occupied().contains(*square)) {
+                problems.push(PositionProblem::ImpossibleEnPassant(pawn));
+            }
+        }
+
+        if self.fifty_move_counter > 100 {
+            problems.push(PositionProblem::FiftyMoveCounterOutOfRange);
+        }
+        return problems;
+    }
+
+    pub fn is_valid(&self) -> bool {
+        return self.validate().is_empty();
+    }
+}
+
+impl Display for PositionProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            PositionProblem::MissingKing(team) => write!(f, "{} has no king", team),
+            PositionProblem::TooManyKings(team) => write!(f, "{} has more than one king", team),
+            PositionProblem::TooManyPieces(team) => write!(f, "{} has more than 16 pieces", team),
+            PositionProblem::TooManyPawns(team) => write!(f, "{} has more than 8 pawns", team),
+            PositionProblem::TooManyPromotions(team) => write!(f, "{} has more promoted pieces than missing pawns", team),
+            PositionProblem::PawnOnBackRank(square) => write!(f, "Pawn on the back rank at {}", square),
+            PositionProblem::OverlappingPieces(square) => write!(f, "More than one piece on {}", square),
+            PositionProblem::OccupancyMismatch(square) => write!(f, "The occupancy board is wrong at {}", square),
+            PositionProblem::OpponentInCheck => write!(f, "The side not to move is in check"),
+            PositionProblem::TooManyCheckers => write!(f, "More than two pieces give check"),
+            PositionProblem::ImpossibleCastling(index) => write!(f, "{} can't castle {} without the king and rook at home",
+                if *index < 2 { Teams::White } else { Teams::Black }, if index % 2 == 0 { "queenside" } else { "kingside" }),
+            PositionProblem::ImpossibleEnPassant(square) => write!(f, "No pawn could have just moved two squares to {}", square),
+            PositionProblem::FiftyMoveCounterOutOfRange => write!(f, "The fifty move counter is over 100"),
+            PositionProblem::HalfmoveClockOutOfRange => write!(f, "The halfmove clock is over {}", MAX_HALFMOVE_CLOCK),
+            PositionProblem::FullmoveNumberOutOfRange => write!(f, "The fullmove number is too large")
+        };
+    }
+}
@@ -0,0 +1,83 @@
+use crate::ChessBoard;
+use crate::attacks::pawn_attacks;
+use crate::bitboard::Bitboard;
+use crate::pieces::Pieces;
+use crate::util::Teams;
+
+//Random keys for hashing positions, generated at compile time so hashes are the same between runs
+const KEYS: [u64; 12 * 64 + 1 + 4 + 8] = generate_keys();
+
+const SIDE_KEY: usize = 12 * 64;
+const CASTLE_KEYS: usize = SIDE_KEY + 1;
+const EN_PASSANT_KEYS: usize = CASTLE_KEYS + 4;
+
+//Splitmix64, which is simple enough to run in a const fn
+const fn generate_keys() -> [u64; 12 * 64 + 1 + 4 + 8] {
+    let mut output = [0u64; 12 * 64 + 1 + 4 + 8];
+    let mut state = 0x9E37_79B9_7F4A_7C15u64;
+    let mut i = 0;
+    while i < output.len() {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut value = state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        output[i] = value ^ (value >> 31);
+        i += 1;
+    }
+    return output;
+}
+
+//Key for a piece on a square, where piece is an index into Board (piece + team)
+#[inline]
+pub fn piece_key(piece: usize, square: usize) -> u64 {
+    return KEYS[piece * 64 + square];
+}
+
+#[inline]
+pub fn side_key() -> u64 {
+    return KEYS[SIDE_KEY];
+}
+
+#[inline]
+pub fn castle_key(index: usize) -> u64 {
+    return KEYS[CASTLE_KEYS + index];
+}
+
+#[inline]
+pub fn en_passant_key(file: usize) -> u64 {
+    return KEYS[EN_PASSANT_KEYS + file];
+}
+
+impl ChessBoard {
+    //Hashes the pieces, side to move, castling rights and en passant file from scratch
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = 0;
+        for piece in 0..12 {
+            for square in Bitboard(self.board.0[piece]) {
+                hash ^= piece_key(piece, square.index());
+            }
+        }
+
+        if let Teams::Black = self.side_to_move() {
+            hash ^= side_key();
+        }
+        for index in 0..4 {
+            if self.castle_status[index] {
+                hash ^= castle_key(index);
+            }
+        }
+
+        return hash ^ self.en_passant_hash();
+    }
+
+    //The en passant part of the hash. It only counts when a pawn can actually take, so identical positions hash the same.
+    pub fn en_passant_hash(&self) -> u64 {
+        let team = self.side_to_move();
+        if let Some(target) = self.en_passant_target(team).first() {
+            if pawn_attacks(!team, target).intersects(self.board.get_board(Pieces::Pawn, team)) {
+                return en_passant_key(target.file().0 as usize);
+            }
+        }
+        return 0;
+    }
+}
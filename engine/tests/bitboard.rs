@@ -0,0 +1,19 @@
+use chess_engine::bitboard::Square;
+use chess_engine::builder::PositionBuilder;
+use chess_engine::pieces::Pieces;
+use chess_engine::util::{Position, Teams};
+
+#[test]
+fn square_from_position() {
+    assert_eq!(Square::try_from(Position(1 << 28)).map(|square| square.to_string()), Ok(String::from("e4")));
+    assert!(Square::try_from(Position(0)).is_err());
+    assert!(Square::try_from(Position(0b101)).is_err());
+    assert_eq!(Square::try_from(Position::from(Square(63))).map(|square| square.0), Ok(63));
+}
+
+#[test]
+fn builder_positions() {
+    assert!(PositionBuilder::new().place(Position(1), Pieces::Rook, Teams::White).is_ok());
+    assert!(PositionBuilder::new().place(Position(0b11), Pieces::Rook, Teams::White).is_err());
+    assert!(PositionBuilder::new().remove(Position(0)).is_err());
+}
@@ -0,0 +1,75 @@
+use chess_engine::ChessBoard;
+use chess_engine::book::{decode_move, encode_move, OpeningBook, PolyglotKeys};
+
+fn play(board: &mut ChessBoard, chess_move: &str) {
+    let chess_move = board.legal_moves().into_iter().find(|legal| legal.to_string() == chess_move).unwrap();
+    board.make_move(&chess_move);
+}
+
+#[test]
+fn starting_position_keys() {
+    let keys = PolyglotKeys::standard();
+    let mut board = ChessBoard::new();
+    assert_eq!(keys.hash(&board), 0x463B_9618_1691_FC9C);
+    play(&mut board, "e2e4");
+    assert_eq!(keys.hash(&board), 0x823C_9B50_FD11_4196);
+}
+
+//The test positions published with the Polyglot book format
+#[test]
+fn published_keys() {
+    let keys = PolyglotKeys::standard();
+    let positions = [
+        ("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2", 0x0756_B944_61C5_0FB0),
+        ("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2", 0x662F_AFB9_65DB_29D4),
+        ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", 0x22A4_8B5A_8E47_FF78),
+        ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3", 0x652A_607C_A3F2_42C1),
+        ("rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4", 0x00FD_D303_C946_BDD9),
+        ("rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3", 0x3C81_23EA_7B06_7637),
+        ("rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4", 0x5C3F_9B82_9B27_9560),
+    ];
+    for (fen, key) in positions {
+        assert_eq!(keys.hash(&ChessBoard::from_fen(fen).unwrap()), key, "{}", fen);
+    }
+}
+
+#[test]
+fn replacement_keys_checked() {
+    assert!(PolyglotKeys::from_bytes(&[0; 781 * 8]).is_err());
+    assert!(PolyglotKeys::from_bytes(&[0; 8]).is_err());
+}
+
+//Polyglot writes castling as the king taking its own rook
+#[test]
+fn castling_moves() {
+    let board = ChessBoard::from_fen("r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1").unwrap();
+    let short = decode_move(&board, 7 | 4 << 6).unwrap();
+    assert_eq!(short.to_string(), "e1g1");
+    assert_eq!(encode_move(&short), 7 | 4 << 6);
+    let long = decode_move(&board, 4 << 6).unwrap();
+    assert_eq!(long.to_string(), "e1c1");
+    assert_eq!(encode_move(&long), 4 << 6);
+    for chess_move in board.legal_moves() {
+        assert!(decode_move(&board, encode_move(&chess_move)) == Some(chess_move));
+    }
+}
+
+//Entries written by hand for the starting position: two good moves, a knight jump that isn't legal and an empty square
+#[test]
+fn book_moves() {
+    let board = ChessBoard::new();
+    let key = PolyglotKeys::standard().hash(&board);
+    let mut bytes = Vec::new();
+    for (raw_move, weight) in [(28 | 12 << 6, 10u16), (27 | 11 << 6, 30), (11 | 1 << 6, 5), (36 | 20 << 6, 50)] {
+        bytes.extend(key.to_be_bytes());
+        bytes.extend((raw_move as u16).to_be_bytes());
+        bytes.extend(weight.to_be_bytes());
+        bytes.extend(0u32.to_be_bytes());
+    }
+    let book = OpeningBook::from_bytes(&bytes).unwrap();
+    assert_eq!(book.entries(&board).len(), 4);
+    let moves: Vec<String> = book.moves(&board).iter().map(|(chess_move, _)| chess_move.to_string()).collect();
+    assert_eq!(moves, ["e2e4", "d2d4"]);
+    assert_eq!(book.best(&board).unwrap().to_string(), "d2d4");
+    assert!(book.moves(&ChessBoard::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap()).is_empty());
+}
@@ -0,0 +1,39 @@
+use chess_engine::ChessBoard;
+use chess_engine::builder::PositionBuilder;
+use chess_engine::game::Game;
+use chess_engine::pieces::Pieces;
+use chess_engine::util::{GameResult, GameStatus, Teams, Termination};
+use chess_engine::validate::PositionProblem;
+
+fn kings_and_rook(halfmoves: u8) -> PositionBuilder {
+    return PositionBuilder::new()
+        .place("e1", Pieces::King, Teams::White).unwrap()
+        .place("a1", Pieces::Rook, Teams::White).unwrap()
+        .place("e8", Pieces::King, Teams::Black).unwrap()
+        .halfmove_clock(halfmoves);
+}
+
+//The builder takes halfmove clocks up to 150 like FEN does, with the board's counter stopping at 100
+#[test]
+fn halfmove_clock_range() {
+    let board = kings_and_rook(120).build().ok().unwrap();
+    assert_eq!(board.fifty_move_counter, 0);
+    assert_eq!(board.to_fen(), ChessBoard::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 120 1").unwrap().to_fen());
+    assert!(kings_and_rook(150).build().is_ok());
+    assert!(kings_and_rook(151).build().err().unwrap() == vec![PositionProblem::HalfmoveClockOutOfRange]);
+
+    assert!(ChessBoard::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 150 1").is_ok());
+    assert!(ChessBoard::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 151 1").is_err());
+}
+
+#[test]
+fn seventy_five_move_rule() {
+    let draw = GameStatus::Finished(GameResult { winner: None, termination: Termination::SeventyFiveMoveRule });
+    for mut game in [kings_and_rook(149).build_game().ok().unwrap(), Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 149 1").unwrap()] {
+        assert_eq!(game.halfmove_clock(), 149);
+        assert!(game.status() == GameStatus::Ongoing);
+        game.play_san("Ra2").unwrap();
+        assert_eq!(game.halfmove_clock(), 150);
+        assert!(game.status() == draw);
+    }
+}
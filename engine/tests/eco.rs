@@ -0,0 +1,34 @@
+use chess_engine::ChessBoard;
+use chess_engine::eco::OpeningTable;
+use chess_engine::game::Game;
+
+fn play(moves: &[&str]) -> Game {
+    let mut game = Game::new();
+    for san in moves {
+        game.play_san(san).unwrap();
+    }
+    return game;
+}
+
+//Every line of the embedded table has to be legal, or every lookup would panic
+#[test]
+fn embedded_table_parses() {
+    assert!(OpeningTable::embedded().len() > 100);
+    assert!(ChessBoard::new().opening().is_none());
+}
+
+#[test]
+fn transpositions() {
+    let main_line = play(&["d4", "Nf6", "c4", "e6"]);
+    let transposed = play(&["c4", "e6", "d4", "Nf6"]);
+    assert_eq!(main_line.opening().unwrap().eco, "E00");
+    assert_eq!(transposed.opening().unwrap().eco, "E00");
+    assert_eq!(transposed.opening().unwrap().name, main_line.opening().unwrap().name);
+}
+
+#[test]
+fn zero_castling() {
+    let table = OpeningTable::parse("C50 \"Italian Game\" 1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. 0-0").unwrap();
+    let game = play(&["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5", "O-O"]);
+    assert_eq!(table.classify(game.last_position()).unwrap().eco, "C50");
+}
@@ -0,0 +1,78 @@
+use chess_engine::ChessBoard;
+use chess_engine::mate::{MateResult, MateSearch};
+
+//Plain minimax: whether the side to move can force mate within the number of moves
+fn mates_within(board: &ChessBoard, moves: u8) -> bool {
+    return board.legal_moves().iter().any(|chess_move| {
+        let mut next = board.clone();
+        next.make_move(chess_move);
+        let replies = next.legal_moves();
+        if replies.is_empty() {
+            return next.in_check();
+        }
+        return moves > 1 && replies.iter().all(|reply| {
+            let mut after = next.clone();
+            after.make_move(reply);
+            return mates_within(&after, moves - 1);
+        });
+    });
+}
+
+//The fewest moves the side to move needs to mate
+fn shortest_mate(board: &ChessBoard, limit: u8) -> Option<u8> {
+    return (1..=limit).find(|moves| mates_within(board, *moves));
+}
+
+//Checks the solver's answer against minimax: the mate is the shortest, each attacker move in the line keeps
+//it the shortest, and each defender move holds out as long as possible
+fn check_mate(fen: &str, expected: u8) {
+    let board = ChessBoard::from_fen(fen).unwrap();
+    let line = match MateSearch::new().solve(&board, expected + 1) {
+        MateResult::Mate(moves, line) => {
+            assert_eq!(moves, expected, "{}", fen);
+            line
+        }
+        _ => panic!("No mate found in {}", fen)
+    };
+    assert_eq!(shortest_mate(&board, expected), Some(expected), "{}", fen);
+    assert_eq!(line.len(), expected as usize * 2 - 1, "{}", fen);
+
+    let mut position = board;
+    for (ply, chess_move) in line.iter().enumerate() {
+        if ply % 2 == 0 {
+            let remaining = expected - ply as u8 / 2;
+            assert_eq!(shortest_mate(&position, remaining), Some(remaining), "{}", position.to_fen());
+        }
+        assert!(position.legal_moves().contains(chess_move), "{}", position.to_fen());
+        position.make_move(chess_move);
+    }
+    assert!(position.in_check() && position.legal_moves().is_empty(), "{}", fen);
+}
+
+#[test]
+fn mate_in_two() {
+    check_mate("8/8/8/8/8/5K2/R7/7k w - - 0 1", 2);
+}
+
+#[test]
+fn mate_in_three() {
+    check_mate("k7/8/8/3K4/8/8/8/7R w - - 0 1", 3);
+}
+
+#[test]
+fn no_mate() {
+    let mut search = MateSearch::new();
+    let board = ChessBoard::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    assert!(matches!(search.solve(&board, 3), MateResult::NoMate));
+    assert!(!mates_within(&board, 3));
+    let board = ChessBoard::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert!(matches!(search.solve(&board, 3), MateResult::NoMate));
+}
+
+#[test]
+fn node_limit() {
+    let mut search = MateSearch::new();
+    search.max_nodes = Some(10);
+    let board = ChessBoard::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    assert!(matches!(search.solve(&board, 3), MateResult::Unknown));
+}
@@ -0,0 +1,110 @@
+use chess_engine::ChessBoard;
+use chess_engine::movelist::MoveList;
+use chess_engine::util::Move;
+
+//Start, Kiwipete and positions 3 to 5 from the Chess Programming Wiki's perft results, which between them have
+//castling, en passant, promotions, pins and discovered checks
+const POSITIONS: [(&str, u32, u64); 5] = [
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 3, 8902),
+    ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 3, 97862),
+    ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 4, 43238),
+    ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 3, 9467),
+    ("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", 3, 62379),
+];
+
+fn perft(board: &ChessBoard, depth: u32) -> u64 {
+    let mut moves = MoveList::new();
+    board.generate_moves(&mut moves);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    return moves.iter().map(|chess_move| {
+        let mut next = board.clone();
+        next.make_move(chess_move);
+        return perft(&next, depth - 1);
+    }).sum();
+}
+
+//Calls the check on every position up to the depth
+fn walk(board: &ChessBoard, depth: u32, check: &dyn Fn(&ChessBoard)) {
+    check(board);
+    if depth == 0 {
+        return;
+    }
+    for chess_move in board.legal_moves() {
+        let mut next = board.clone();
+        next.make_move(&chess_move);
+        walk(&next, depth - 1, check);
+    }
+}
+
+fn sorted(moves: &MoveList) -> Vec<String> {
+    let mut output: Vec<String> = moves.iter().map(Move::to_string).collect();
+    output.sort();
+    return output;
+}
+
+#[test]
+fn perft_positions() {
+    for (fen, depth, nodes) in POSITIONS {
+        assert_eq!(perft(&ChessBoard::from_fen(fen).unwrap(), depth), nodes, "{}", fen);
+    }
+}
+
+//Captures and quiets split the moves between them, with nothing left out or in both
+#[test]
+fn stages_partition_moves() {
+    for (fen, _, _) in POSITIONS {
+        walk(&ChessBoard::from_fen(fen).unwrap(), 2, &|board| {
+            let (mut all, mut captures, mut quiets) = (MoveList::new(), MoveList::new(), MoveList::new());
+            board.generate_moves(&mut all);
+            board.generate_captures(&mut captures);
+            board.generate_quiets(&mut quiets);
+            assert_eq!(captures.len() + quiets.len(), all.len(), "{}", board.to_fen());
+            let mut both = sorted(&captures);
+            both.extend(sorted(&quiets));
+            both.sort();
+            assert_eq!(both, sorted(&all), "{}", board.to_fen());
+        });
+    }
+}
+
+//The check generator finds exactly the legal moves that leave the other side in check. The extra positions
+//check by castling and by en passant uncovering a bishop or clearing a rank for a rook.
+#[test]
+fn checks() {
+    let extra = ["5k2/8/8/8/8/8/8/4K2R w K - 0 1", "7k/8/8/3pP3/8/8/8/B3K3 w - d6 0 1", "8/8/8/R2pP2k/8/8/8/4K3 w - d6 0 1"];
+    for fen in POSITIONS.iter().map(|(fen, _, _)| *fen).chain(extra) {
+        walk(&ChessBoard::from_fen(fen).unwrap(), 2, &|board| {
+            let mut checks = MoveList::new();
+            board.generate_checks(&mut checks);
+            let mut expected = MoveList::new();
+            for chess_move in board.legal_moves() {
+                let mut next = board.clone();
+                next.make_move(&chess_move);
+                if next.in_check() {
+                    expected.push(chess_move);
+                }
+            }
+            assert_eq!(sorted(&checks), sorted(&expected), "{}", board.to_fen());
+        });
+    }
+}
+
+//The legality test agrees with making each move and looking at the king. The extra positions have an en passant
+//capture that would expose the king along the rank, a double check and a king stepping back along a checking line.
+#[test]
+fn legality() {
+    let extra = ["8/8/8/KPp4r/8/8/8/7k w - c6 0 1", "4k3/8/8/8/1b6/8/3N4/r3K3 w - - 0 1", "8/8/8/8/8/8/1k6/r3K3 w - - 0 1"];
+    for fen in POSITIONS.iter().map(|(fen, _, _)| *fen).chain(extra) {
+        walk(&ChessBoard::from_fen(fen).unwrap(), 2, &|board| {
+            let team = board.side_to_move();
+            for chess_move in board.pseudo_legal_moves(team) {
+                let mut next = board.clone();
+                next.make_move(&chess_move);
+                let safe = !next.is_attacked(next.king_square(team).unwrap(), !team);
+                assert_eq!(board.is_legal(&chess_move), safe, "{} {}", board.to_fen(), chess_move);
+            }
+        });
+    }
+}
@@ -0,0 +1,12 @@
+use chess_engine::PIECES;
+use chess_engine::pieces::Pieces;
+use chess_engine::util::Teams;
+
+//Indexed like the piece boards, with the white symbols for White
+#[test]
+fn piece_symbols() {
+    assert_eq!(PIECES[Pieces::Pawn as usize + Teams::White as usize], '♙');
+    assert_eq!(PIECES[Pieces::King as usize + Teams::White as usize], '♔');
+    assert_eq!(PIECES[Pieces::Pawn as usize + Teams::Black as usize], '♟');
+    assert_eq!(PIECES[Pieces::Queen as usize + Teams::Black as usize], '♛');
+}
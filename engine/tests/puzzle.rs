@@ -0,0 +1,74 @@
+use chess_engine::game::Game;
+use chess_engine::pgn::{PgnGame, read_games};
+use chess_engine::puzzle::{Puzzle, PuzzleSettings, game_puzzles};
+use chess_engine::search::{Search, SearchLimits};
+
+fn read_game(text: &str) -> PgnGame {
+    let mut games = read_games(text);
+    assert_eq!(games.len(), 1);
+    return games.remove(0).unwrap();
+}
+
+fn puzzles(text: &str) -> Vec<Puzzle> {
+    let mut settings = PuzzleSettings::new(SearchLimits::depth(3));
+    settings.min_ply = 0;
+    return game_puzzles(&read_game(text), &mut Search::new(SearchLimits::depth(3)), &settings);
+}
+
+#[test]
+fn pgn_round_trip() {
+    let mut game = Game::new();
+    for san in ["e4", "c5", "Nf3", "d6", "d4", "cxd4", "Nxd4", "Nf6", "Nc3", "a6"] {
+        game.play_san(san).unwrap();
+    }
+    assert!(game.set_comment(3, Some(String::from("the Najdorf"))));
+    let mut from_position = Game::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    from_position.play_san("O-O").unwrap();
+    from_position.play_san("Kd7").unwrap();
+
+    let text = format!("{}\n{}", game.to_pgn(&[("Event", "Round \"1\"")]), from_position.to_pgn(&[("Event", "Two")]));
+    let games: Vec<PgnGame> = read_games(&text).into_iter().map(Result::unwrap).collect();
+    assert_eq!(games.len(), 2);
+    for (read, written) in games.iter().zip([&game, &from_position]) {
+        assert_eq!(read.game.start().to_fen(), written.start().to_fen());
+        assert_eq!(read.game.len(), written.len());
+        for (read, written) in read.game.moves().iter().zip(written.moves()) {
+            assert!(read.chess_move == written.chess_move);
+            assert_eq!(read.san, written.san);
+            assert_eq!(read.comment, written.comment);
+        }
+    }
+    assert_eq!(games[0].tag("Event"), Some("Round \"1\""));
+    assert_eq!(games[0].tag("ECO"), Some("B90"));
+    assert_eq!(games[1].tag("SetUp"), Some("1"));
+}
+
+//Black's last move hangs mate, which White takes in one game and misses in the other
+#[test]
+fn known_blunder() {
+    let taken = puzzles("[Event \"Taken\"]\n\n1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0\n");
+    let mate = taken.iter().find(|puzzle| puzzle.ply == 6).unwrap();
+    assert!(mate.found);
+    assert_eq!(mate.solution.len(), 1);
+    assert_eq!(mate.solution[0].to_string(), "h5f7");
+    assert_eq!(mate.event.as_deref(), Some("Taken"));
+
+    let missed = puzzles("1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qf3 *\n");
+    let mate = missed.iter().find(|puzzle| puzzle.ply == 6).unwrap();
+    assert!(!mate.found);
+    assert_eq!(mate.played.to_string(), "h5f3");
+    assert_eq!(mate.solution[0].to_string(), "h5f7");
+    for puzzle in taken.iter().chain(&missed) {
+        assert!(puzzle.solution.len() % 2 == 1 && puzzle.margin >= 200);
+    }
+}
+
+//Legal's mate: every White move of the line is the only one that mates
+#[test]
+fn solution_line() {
+    let puzzles = puzzles("1. e4 e5 2. Nf3 d6 3. Bc4 Bg4 4. Nc3 g6 5. Nxe5 Bxd1 6. Bxf7+ Ke7 7. Nd5# 1-0\n");
+    let mate = puzzles.iter().find(|puzzle| puzzle.ply == 10).unwrap();
+    let solution: Vec<String> = mate.solution.iter().map(|chess_move| chess_move.to_string()).collect();
+    assert_eq!(solution, ["c4f7", "e8e7", "c3d5"]);
+    assert!(mate.found);
+}
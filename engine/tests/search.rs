@@ -0,0 +1,26 @@
+use chess_engine::ChessBoard;
+use chess_engine::search::{Search, SearchLimits};
+
+//With one thread the node limit is checked on every node, so the search stops exactly on it
+#[test]
+fn exact_node_limit() {
+    for limit in [5000, 12345] {
+        let result = Search::new(SearchLimits::nodes(limit)).run(&ChessBoard::new());
+        assert!(result.best_move.is_some());
+        assert_eq!(result.nodes, limit);
+        assert_eq!(result.thread_nodes, vec![limit]);
+        assert!(result.quiescence_nodes < limit);
+    }
+}
+
+#[test]
+fn thread_node_counts() {
+    let mut search = Search::new(SearchLimits::nodes(20000));
+    search.threads = 3;
+    let result = search.run(&ChessBoard::new());
+    assert!(result.best_move.is_some());
+    assert_eq!(result.thread_nodes.len(), 3);
+    assert_eq!(result.thread_nodes.iter().sum::<u64>(), result.nodes);
+    //The helpers can run on a little after the main thread has seen the limit
+    assert!(result.nodes >= 20000);
+}
@@ -0,0 +1,32 @@
+use chess_engine::ChessBoard;
+
+fn see(fen: &str, chess_move: &str) -> i32 {
+    let board = ChessBoard::from_fen(fen).unwrap();
+    let chess_move = board.legal_moves().into_iter().find(|legal| legal.to_string() == chess_move).unwrap();
+    return board.see(&chess_move);
+}
+
+#[test]
+fn undefended() {
+    assert_eq!(see("4k3/8/8/3n4/4P3/8/8/4K3 w - - 0 1", "e4d5"), 320);
+}
+
+#[test]
+fn defended_by_pawn() {
+    assert_eq!(see("4k3/8/2p5/3n4/4P3/8/8/4K3 w - - 0 1", "e4d5"), 220);
+    assert_eq!(see("4k3/8/2p5/3p4/8/8/8/3RK3 w - - 0 1", "d1d5"), -400);
+}
+
+//The rook behind the first one recaptures once the first has gone
+#[test]
+fn x_ray() {
+    assert_eq!(see("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5"), 100);
+    assert_eq!(see("3rk3/8/8/3p4/8/8/3R4/4K3 w - - 0 1", "d2d5"), -400);
+}
+
+//The king can't take a piece that's still defended
+#[test]
+fn king_recapture() {
+    assert_eq!(see("4k3/3p4/8/8/8/8/8/3RK3 w - - 0 1", "d1d7"), -400);
+    assert_eq!(see("4k3/3p4/8/8/8/8/3R4/3RK3 w - - 0 1", "d2d7"), 100);
+}
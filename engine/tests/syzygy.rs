@@ -0,0 +1,90 @@
+use std::env;
+use chess_engine::ChessBoard;
+use chess_engine::syzygy::{Tablebase, Wdl};
+
+//These need real table files, which are too big to keep in the repository, so they only run with --ignored.
+//Point SYZYGY_PATH at a directory with at least KQvK, KRvK and KPvK (WDL and DTZ).
+fn tablebase() -> Tablebase {
+    let directory = env::var("SYZYGY_PATH").expect("SYZYGY_PATH has to be set to run the tablebase tests");
+    let tablebase = Tablebase::open(&directory).unwrap();
+    assert!(tablebase.max_pieces() >= 3, "No 3 piece tables in {}", directory);
+    return tablebase;
+}
+
+fn board(fen: &str) -> ChessBoard {
+    return ChessBoard::from_fen(fen).unwrap();
+}
+
+#[test]
+#[ignore]
+fn wdl() {
+    let tablebase = tablebase();
+    let positions = [
+        ("4k3/8/8/8/8/8/8/4K2Q w - - 0 1", Wdl::Win),
+        ("4k3/8/8/8/8/8/8/4K2Q b - - 0 1", Wdl::Loss),
+        ("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", Wdl::Win),
+        ("4k3/8/8/8/8/8/8/R3K3 b - - 0 1", Wdl::Loss),
+        //The rook is hanging
+        ("8/8/8/8/8/8/1k6/R3K3 b - - 0 1", Wdl::Draw),
+        //The king on the sixth rank in front of its pawn wins whoever is to move
+        ("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1", Wdl::Win),
+        ("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1", Wdl::Loss),
+        ("4k3/8/4P3/4K3/8/8/8/8 w - - 0 1", Wdl::Draw),
+        //A rook pawn can't be won against a king in the corner
+        ("7k/8/8/8/8/8/7P/7K w - - 0 1", Wdl::Draw),
+    ];
+    for (fen, wdl) in positions {
+        assert!(tablebase.probe_wdl(&board(fen)) == Some(wdl), "{}", fen);
+    }
+}
+
+#[test]
+#[ignore]
+fn dtz() {
+    let tablebase = tablebase();
+    assert_eq!(tablebase.probe_dtz(&board("k7/8/1K6/8/8/8/8/7Q w - - 0 1")), Some(1));
+    assert_eq!(tablebase.probe_dtz(&board("8/8/8/8/8/8/1k6/R3K3 b - - 0 1")), Some(0));
+    assert_eq!(tablebase.probe_dtz(&board("7k/8/8/8/8/8/7P/7K w - - 0 1")), Some(0));
+    assert_eq!(tablebase.probe_dtz(&board("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1")).map(i32::signum), Some(1));
+}
+
+//Following the best moves has to mate within the DTZ of the start, with the winner always winning and the loser always losing
+#[test]
+#[ignore]
+fn best_line() {
+    let tablebase = tablebase();
+    for fen in ["4k3/8/8/8/8/8/8/4K2Q w - - 0 1", "8/8/3k4/8/8/8/8/R3K3 w - - 0 1"] {
+        let mut board = board(fen);
+        let start = tablebase.probe_dtz(&board).unwrap();
+        assert!(start > 0, "{}", fen);
+        let mut dtz = start;
+        let mut plies = 0;
+        while !board.legal_moves().is_empty() {
+            let best = tablebase.best_move(&board).unwrap();
+            assert!(best.wdl == if dtz > 0 { Wdl::Win } else { Wdl::Loss }, "{}", board.to_fen());
+            board.make_move(&best.chess_move);
+            plies += 1;
+            if board.legal_moves().is_empty() {
+                break;
+            }
+            let next = tablebase.probe_dtz(&board).unwrap();
+            assert_eq!(next.signum(), -dtz.signum(), "{}", board.to_fen());
+            dtz = next;
+        }
+        assert!(board.in_check(), "{}", fen);
+        assert!(plies <= start + 1, "{}", fen);
+    }
+}
+
+//A win turns into a cursed win once the fifty move rule would step in before the next capture or pawn move
+#[test]
+#[ignore]
+fn fifty_move_rule() {
+    let tablebase = tablebase();
+    let board = board("8/8/3k4/8/8/8/8/R3K3 w - - 0 1");
+    let best = tablebase.best_move(&board).unwrap();
+    let mut late = ChessBoard::from_fen(&format!("8/8/3k4/8/8/8/8/R3K3 w - - {} 1", 99 - best.dtz)).unwrap();
+    assert!(tablebase.best_move(&late).unwrap().wdl == Wdl::Win);
+    late = ChessBoard::from_fen(&format!("8/8/3k4/8/8/8/8/R3K3 w - - {} 1", 101 - best.dtz)).unwrap();
+    assert!(tablebase.best_move(&late).unwrap().wdl == Wdl::CursedWin);
+}
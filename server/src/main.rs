@@ -1,8 +1,43 @@
-use chess_engine::{ChessBoard, GameStatus, Pieces, Teams};
+use chess_engine::{ChessBoard, Pieces, Teams};
+use chess_engine::util::Termination;
 use chess_engine::notation::parse_notation;
+use chess_engine::pgn::{PgnGame, read_games};
+use std::env;
+use std::fs;
+use std::process;
+use std::str::FromStr;
+
+mod openings;
+mod puzzles;
 
 pub fn main() {
-    let board = ChessBoard::new();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("puzzles") => Some(puzzles::run(&args[1..])),
+        Some("openings") => Some(openings::run(&args[1..])),
+        _ => None
+    };
+    if let Some(result) = result {
+        if let Err(error) = result {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+        return;
+    }
+
+    //A custom start position can be given with --fen. FEN import validates it, so nothing gets played from an
+    //impossible position.
+    let board = match args.iter().position(|arg| arg == "--fen") {
+        Some(index) => match parse_value::<String>("--fen", args.get(index + 1), "Usage: --fen FEN")
+            .and_then(|fen| ChessBoard::from_fen(&fen)) {
+            Ok(board) => board,
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        },
+        None => ChessBoard::new()
+    };
     //11111111
     //11111111
     //00000000
@@ -20,9 +55,34 @@ pub fn main() {
 fn notation_test() {
     let board = parse_notation("1. e4 c5 2. d3 f6 3. f3 d6 4. Be2 d5 5. exd5 e6 6. dxe6 Qd6 7. d4 Qxe6 8. f4 Qd6 9. Bb5+ Bd7 10. Bxd7+ Qxd7 11. Qe2+ Kd8 12. Nf3 g5 13. Ne5 Be7 14. Qc4 a6 15. Qxc5 g4 16. Rf1 g3 17. Rf3 h6 18. Rxg3 h5 19. Rxg8+ Bf8 20. Rg3 h4 21. Re3 f5 22. Nf7+ Qxf7 23. Nc3 Rh7 24. Nd5 Rh8 25. Nf6 Rh6 26. Nh7 Rh5 27. Ng5 Rh6 28. d5 Rh8 29. Re7 Rh7 30. Nxf7+ Rxf7 31. Rxf7 Ke8 32. Qe7+ Bxe7 33. d6 Bf6 34. d7+ Kxf7 35. d8=Q Nc6 36. c4 Nb4 37. c5 Nxa2 38. c6 Nb4 39. c7 Nc2+ 40. Kd2 a5 41. c8=Q Kg7 42. Qh8+ Kf7 43. Qxa8 b6 44. Qxa5 b5 45. Qxb5 Be5 46. Qd5+ Ke7 47. Qdxe5+ Kf7 48. Qxf5+ Ke7 49. Qxc2 h3 50. gxh3 Ke6 51. Qd3 Ke7 52. Qg8 Kf6 53. Qe4");
 
-    if let GameStatus::DrawByStalemate = board.game_status {
+    if board.game_status.termination() == Some(Termination::Stalemate) {
         println!("Success!");
     } else {
         println!("Failure!");
     }
 }
+
+//Every game in the PGN files. Games that can't be read are reported and skipped, so one bad game doesn't lose
+//the rest of an archive.
+fn read_archives(paths: &[&String]) -> Result<Vec<PgnGame>, String> {
+    let mut output = Vec::new();
+    for path in paths {
+        let text = fs::read_to_string(path).map_err(|error| format!("Failed to read {}: {}", path, error))?;
+        for game in read_games(&text) {
+            match game {
+                Ok(game) => output.push(game),
+                Err(error) => eprintln!("Skipped {}: {}", path, error)
+            }
+        }
+    }
+    return Ok(output);
+}
+
+//The value after a command line option
+fn parse_value<T: FromStr>(option: &str, value: Option<&String>, usage: &str) -> Result<T, String> {
+    return match value.map(|value| value.parse::<T>()) {
+        Some(Ok(value)) => Ok(value),
+        Some(Err(_)) => Err(format!("Invalid value {} for {}", value.unwrap(), option)),
+        None => Err(format!("Missing value for {}\n{}", option, usage))
+    };
+}
@@ -0,0 +1,49 @@
+use std::collections::BTreeSet;
+use chess_engine::eco::{OpeningTable, results_by_opening};
+use crate::{parse_value, read_archives};
+
+const USAGE: &str = "Usage: openings [--player NAME] [--table FILE] PGN...";
+
+//Tournament report of how each bot did in each opening it played, from the PGN the server wrote.
+//The embedded opening table is used unless another one in scid's format is given.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut player: Option<String> = None;
+    let mut table_path: Option<String> = None;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--player" => player = Some(parse_value(arg, args.next(), USAGE)?),
+            "--table" => table_path = Some(parse_value(arg, args.next(), USAGE)?),
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}\n{}", arg, USAGE)),
+            _ => paths.push(arg)
+        }
+    }
+    if paths.is_empty() {
+        return Err(String::from(USAGE));
+    }
+
+    let loaded;
+    let table = match table_path {
+        Some(path) => {
+            loaded = OpeningTable::load(&path)?;
+            &loaded
+        }
+        None => OpeningTable::embedded()
+    };
+    let games = read_archives(&paths)?;
+    let players: BTreeSet<&str> = match &player {
+        Some(player) => BTreeSet::from([player.as_str()]),
+        None => games.iter().flat_map(|game| [game.tag("White"), game.tag("Black")]).flatten().collect()
+    };
+
+    for player in players {
+        println!("{}", player);
+        println!("  {:<4} {:<48} {:>5} {:>5} {:>5} {:>6}", "ECO", "Opening", "Games", "Wins", "Draws", "Losses");
+        for stats in results_by_opening(&games, player, table) {
+            println!("  {:<4} {:<48} {:>5} {:>5} {:>5} {:>6}", stats.eco, stats.name, stats.games, stats.wins, stats.draws, stats.losses);
+        }
+        println!();
+    }
+    return Ok(());
+}
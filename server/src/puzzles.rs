@@ -0,0 +1,41 @@
+use std::fs;
+use chess_engine::puzzle::{PuzzleSettings, find_puzzles, to_csv, to_json};
+use chess_engine::search::{Search, SearchLimits};
+use crate::{parse_value, read_archives};
+
+const USAGE: &str = "Usage: puzzles [--csv] [--depth PLIES] [--margin CENTIPAWNS] [--player NAME] [--min-ply PLIES] [--output FILE] PGN...";
+
+//Scans PGN collections for puzzles and writes them as JSON, or as CSV with --csv
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut settings = PuzzleSettings::new(SearchLimits::depth(5));
+    let mut csv = false;
+    let mut output = None;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--csv" => csv = true,
+            "--depth" => settings.limits = SearchLimits::depth(parse_value(arg, args.next(), USAGE)?),
+            "--margin" => settings.margin = parse_value(arg, args.next(), USAGE)?,
+            "--player" => settings.player = Some(parse_value(arg, args.next(), USAGE)?),
+            "--min-ply" => settings.min_ply = parse_value(arg, args.next(), USAGE)?,
+            "--output" => output = Some(parse_value::<String>(arg, args.next(), USAGE)?),
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}\n{}", arg, USAGE)),
+            _ => paths.push(arg)
+        }
+    }
+    if paths.is_empty() {
+        return Err(String::from(USAGE));
+    }
+
+    let games = read_archives(&paths)?;
+    let mut search = Search::new(settings.limits);
+    let puzzles = find_puzzles(&games, &mut search, &settings);
+    eprintln!("Found {} puzzles in {} games", puzzles.len(), games.len());
+    let text = if csv { to_csv(&puzzles) } else { to_json(&puzzles) };
+    match output {
+        Some(path) => fs::write(&path, text).map_err(|error| format!("Failed to write {}: {}", path, error))?,
+        None => print!("{}", text)
+    }
+    return Ok(());
+}